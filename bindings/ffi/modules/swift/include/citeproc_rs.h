@@ -88,6 +88,9 @@ typedef CF_ENUM(uint8_t, CROutputFormat) {
   CROutputFormat_Html,
   CROutputFormat_Rtf,
   CROutputFormat_Plain,
+  CROutputFormat_Latex,
+  CROutputFormat_Typst,
+  CROutputFormat_AsciiDoc,
 };
 
 /**
@@ -218,6 +218,9 @@ pub enum OutputFormat {
     Html,
     Rtf,
     Plain,
+    Latex,
+    Typst,
+    AsciiDoc,
 }
 
 #[repr(C)]
@@ -236,6 +239,9 @@ impl OutputFormat {
             OutputFormat::Html => rust::SupportedFormat::Html,
             OutputFormat::Rtf => rust::SupportedFormat::Rtf,
             OutputFormat::Plain => rust::SupportedFormat::Plain,
+            OutputFormat::Latex => rust::SupportedFormat::Latex,
+            OutputFormat::Typst => rust::SupportedFormat::Typst,
+            OutputFormat::AsciiDoc => rust::SupportedFormat::AsciiDoc,
         }
     }
 }
@@ -258,6 +258,7 @@ pub mod string_id {
     pub struct FullRender {
         pub all_clusters: FnvHashMap<SmartString, Arc<SmartString>>,
         pub bib_entries: Vec<BibEntry<Markup>>,
+        pub warnings: Vec<super::Warning>,
     }
 
     #[derive(Debug, thiserror::Error)]
@@ -333,6 +334,70 @@ pub struct BibEntry<O: OutputFormat = Markup> {
 pub struct FullRender {
     pub all_clusters: FnvHashMap<ClusterId, Arc<SmartString>>,
     pub bib_entries: Vec<BibEntry<Markup>>,
+    pub warnings: Vec<Warning>,
+}
+
+/// A non-fatal problem with the style or the references. Rendering carries on regardless. See
+/// [`Processor::full_render`][crate::Processor::full_render].
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum Warning {
+    /// A `<text term="..."/>` names a term that the locale for `lang` does not define, so it
+    /// renders nothing in that language.
+    MissingTerm {
+        term: SmartString,
+        lang: SmartString,
+    },
+    /// A `<macro>` that no layout, sort key or other macro calls.
+    UnusedMacro { name: SmartString },
+    /// A rendered reference has a field that is not a CSL variable, so no style can render it.
+    /// There is no style-side counterpart: a style naming an unknown variable fails to parse.
+    UnknownVariable { id: Atom, name: SmartString },
+}
+
+/// A summary of how disambiguation resolved for every cited reference, for snapshot-testing a
+/// style against a corpus. See [`Processor::disambiguation_report`][crate::Processor::disambiguation_report].
+#[derive(Serialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DisambReport {
+    /// One entry per cited reference, sorted by reference id.
+    pub references: Vec<RefDisambReport>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RefDisambReport {
+    pub id: Atom,
+    /// Every pass that ran on any cite of this reference, in the order the processor runs them.
+    pub passes: Vec<DisambPassKind>,
+    /// The year suffix assigned to this reference, e.g. `"a"`.
+    pub year_suffix: Option<SmartString>,
+    /// The pass that finally told this reference's cites apart from the others. `None` if they
+    /// were never ambiguous, or still are. When cites differ, the latest pass wins.
+    pub resolved_by: Option<DisambPassKind>,
+    /// True if any cite of this reference could still match another reference after all passes.
+    pub ambiguous: bool,
+}
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum DisambPassKind {
+    AddNames,
+    AddGivenName,
+    AddYearSuffix,
+    Conditionals,
+}
+
+impl From<&citeproc_proc::DisambPass> for DisambPassKind {
+    fn from(pass: &citeproc_proc::DisambPass) -> Self {
+        use citeproc_proc::DisambPass;
+        match pass {
+            DisambPass::AddNames => DisambPassKind::AddNames,
+            DisambPass::AddGivenName(_) => DisambPassKind::AddGivenName,
+            DisambPass::AddYearSuffix(_) => DisambPassKind::AddYearSuffix,
+            DisambPass::Conditionals => DisambPassKind::Conditionals,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, Ord, PartialOrd, PartialEq)]
@@ -359,6 +424,9 @@ pub enum SupportedFormat {
     Html,
     Rtf,
     Plain,
+    Latex,
+    Typst,
+    AsciiDoc,
 }
 
 impl Default for SupportedFormat {
@@ -373,6 +441,9 @@ impl SupportedFormat {
             SupportedFormat::Html => Markup::Html(options),
             SupportedFormat::Rtf => Markup::Rtf(options),
             SupportedFormat::Plain => Markup::Plain(options),
+            SupportedFormat::Latex => Markup::Latex(options),
+            SupportedFormat::Typst => Markup::Typst(options),
+            SupportedFormat::AsciiDoc => Markup::AsciiDoc(options),
         }
     }
 }
@@ -384,6 +455,9 @@ impl FromStr for SupportedFormat {
             "html" => Ok(SupportedFormat::Html),
             "rtf" => Ok(SupportedFormat::Rtf),
             "plain" => Ok(SupportedFormat::Plain),
+            "latex" => Ok(SupportedFormat::Latex),
+            "typst" => Ok(SupportedFormat::Typst),
+            "asciidoc" => Ok(SupportedFormat::AsciiDoc),
             _ => Err(()),
         }
     }
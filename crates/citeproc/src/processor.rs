@@ -11,8 +11,9 @@
 use crate::prelude::*;
 
 use crate::api::{
-    string_id, BibEntry, BibliographyMeta, BibliographyUpdate, ClusterPosition, IncludeUncited,
-    ReorderingError, SecondFieldAlign, UpdateSummary,
+    string_id, BibEntry, BibliographyMeta, BibliographyUpdate, ClusterPosition, DisambPassKind,
+    DisambReport, FullRender, IncludeUncited, RefDisambReport, ReorderingError, SecondFieldAlign,
+    UpdateSummary, Warning,
 };
 use citeproc_db::{
     CiteData, CiteDatabaseStorage, HasFetcher, LocaleDatabaseStorage, StyleDatabaseStorage, Uncited,
@@ -28,7 +29,10 @@ use salsa::{ParallelDatabase, Snapshot};
 use std::sync::Arc;
 use std::sync::{Mutex, RwLock};
 
-use csl::{Lang, Style, StyleError};
+use csl::{
+    AnyVariable, Collapse, Element, Lang, Locale, SortSource, Style, StyleError, TextSource,
+    TextTermSelector,
+};
 
 use citeproc_io::output::{markup::Markup, OutputFormat};
 use citeproc_io::{Cite, ClusterMode, Reference, SmartString};
@@ -58,6 +62,24 @@ impl SavedBib {
     }
 }
 
+fn diff_bibliography(old: &SavedBib, new: &SavedBib) -> Option<BibliographyUpdate> {
+    let mut update = BibliographyUpdate::new();
+    for (k, v) in new.bib_entries.iter() {
+        let old_v = old.bib_entries.get(k);
+        if Some(v) != old_v {
+            update.updated_entries.insert(k.clone(), v.clone());
+        }
+    }
+    if new.sorted_refs.0 != old.sorted_refs.0 {
+        update.entry_ids = Some(new.sorted_refs.0.clone());
+    }
+    if update.updated_entries.is_empty() && update.entry_ids.is_none() {
+        None
+    } else {
+        Some(update)
+    }
+}
+
 #[salsa::database(
     StyleDatabaseStorage,
     LocaleDatabaseStorage,
@@ -133,6 +155,9 @@ pub struct InitOptions<'a> {
     pub style: &'a str,
     /// You might get this from a dependent style via `StyleMeta::parse(dependent_xml_string)`
     pub locale_override: Option<Lang>,
+    /// Renders the bibliography in a different language to the citations. Defaults to the
+    /// citation language, i.e. `locale_override` or the style's `default-locale`.
+    pub bibliography_locale_override: Option<Lang>,
     /// Mechanism for fetching the locale you provide, if necessary.
     pub fetcher: Option<Arc<dyn LocaleFetcher>>,
 
@@ -184,6 +209,7 @@ impl Processor {
         let InitOptions {
             style,
             locale_override,
+            bibliography_locale_override,
             fetcher,
             format,
             format_options,
@@ -208,6 +234,10 @@ impl Processor {
         )?;
         db.set_style_with_durability(Arc::new(style), Durability::HIGH);
         db.set_default_lang_override_with_durability(locale_override, Durability::HIGH);
+        db.set_bibliography_lang_override_with_durability(
+            bibliography_locale_override,
+            Durability::HIGH,
+        );
         db.set_bibliography_no_sort_with_durability(bibliography_no_sort, Durability::HIGH);
         Ok(db)
     }
@@ -218,6 +248,13 @@ impl Processor {
         Ok(())
     }
 
+    /// Caps how many extra names the add-names disambiguation pass may add to a single names
+    /// block. Past the cap, cites are left ambiguous instead of expanding further. `None` (the
+    /// default) means no limit.
+    pub fn limit_add_names(&mut self, limit: Option<u16>) {
+        self.set_add_names_limit_with_durability(limit, Durability::HIGH);
+    }
+
     #[cfg(feature = "rayon")]
     fn snap(&self) -> Snap {
         Snap(self.snapshot())
@@ -264,6 +301,16 @@ impl Processor {
                     snap.0.ir_gen2_add_given_name(cite_id);
                 });
             self.year_suffixes();
+            self.disambiguated_person_names();
+            // Cites in different groups cannot affect each other's disambiguation, so give each
+            // group a thread of its own rather than having threads wait on each other's queries.
+            self.disamb_groups()
+                .par_iter()
+                .for_each_with(self.snap(), |snap, group| {
+                    for &cite_id in group {
+                        snap.0.ir_fully_disambiguated(cite_id);
+                    }
+                });
             clusters
                 .par_iter()
                 .map_with(self.snap(), |snap, cluster| {
@@ -394,6 +441,48 @@ impl Processor {
         self.set_all_keys_with_durability(Arc::new(keys), Durability::MEDIUM);
     }
 
+    /// Inserts a reference and returns only the bibliography entries that changed, for building
+    /// up a bibliography one reference at a time. Entries are cached, so this does not re-render
+    /// the whole bibliography. The new entry can still change its neighbours: a new first work by
+    /// an author moves the subsequent-author-substitute onto the next one, and sorting can
+    /// renumber later entries. Any such entries are in `updated_entries` too, and `entry_ids` is
+    /// set when the order changed.
+    ///
+    /// The reference only enters the bibliography once it is cited or included through
+    /// [`Processor::include_uncited`]. This has no effect on what
+    /// [`Processor::batched_updates`] reports.
+    pub fn insert_reference_with_bib_update(
+        &mut self,
+        refr: Reference,
+    ) -> Option<BibliographyUpdate> {
+        if self.get_style().bibliography.is_none() {
+            self.insert_reference(refr);
+            return None;
+        }
+        let old = SavedBib {
+            sorted_refs: self.sorted_refs(),
+            bib_entries: self.get_bibliography_map(),
+        };
+        self.insert_reference(refr);
+        let new = SavedBib {
+            sorted_refs: self.sorted_refs(),
+            bib_entries: self.get_bibliography_map(),
+        };
+        diff_bibliography(&old, &new)
+    }
+
+    /// Sets flags on a reference for `<if flag="...">` to test, with the `reference-flags`
+    /// feature enabled. They replace any flags set before. Does nothing if the reference has not
+    /// been inserted; inserting the reference again clears them.
+    pub fn set_reference_flags(&mut self, id: Atom, flags: FnvHashSet<String>) {
+        if !self.all_keys().contains(&id) {
+            return;
+        }
+        let mut refr = Reference::clone(&self.reference_input(id.clone()));
+        refr.flags = flags;
+        self.set_reference_input_with_durability(id, Arc::new(refr), Durability::MEDIUM);
+    }
+
     pub fn remove_reference(&mut self, id: Atom) {
         let keys = self.all_keys();
         let mut keys = IndexSet::clone(&keys);
@@ -550,8 +639,52 @@ impl Processor {
         self.insert_cites(interned, cites);
     }
 
+    /// Adds one cite to the end of an existing cluster, e.g. while a user is typing inside a
+    /// citation.
+    ///
+    /// The cites already in the cluster keep their `CiteId`s, so the only new work is rendering
+    /// the new cite and rebuilding this cluster. Document-wide queries (positions, year
+    /// suffixes, bibliography numbering) are re-checked, but anything depending on them is only
+    /// re-rendered if their results actually change, i.e. if the new cite introduces a new
+    /// reference that changes ambiguity or numbering, or changes the position of a later cite.
+    pub fn append_cite_to_cluster(&mut self, cluster_id: ClusterId, cite: Cite<Markup>) {
+        if !self.all_cluster_ids().contains(&cluster_id) {
+            return self.insert_cites(cluster_id, &[cite]);
+        }
+        let mut ids = (*self.cluster_cites(cluster_id)).clone();
+        let cite_id = self.cite(CiteData::RealCite {
+            cluster: cluster_id,
+            index: ids.len() as u32,
+            cite: Arc::new(cite),
+        });
+        ids.push(cite_id);
+        self.set_cluster_cites(cluster_id, Arc::new(ids));
+    }
+
+    pub fn append_cite_to_cluster_str(&mut self, cluster_id: &str, cite: Cite<Markup>) {
+        let interned = self.cluster_id(cluster_id);
+        self.append_cite_to_cluster(interned, cite);
+    }
+
     // Getters, because the query groups have too much exposed to publish.
 
+    /// Whether applying `collapse` to a cluster would alter its rendered output, compared to
+    /// rendering it without any cite grouping or collapsing. Useful for UI that toggles
+    /// collapsing. Returns false if the cluster has not been assigned a position in the document.
+    pub fn would_collapse_change(&self, cluster_id: ClusterId, collapse: Collapse) -> bool {
+        self.cluster_note_number(cluster_id).is_some()
+            && citeproc_proc::would_collapse_change(self, cluster_id, collapse)
+    }
+
+    /// The delimiters written between the cites of a cluster after grouping and collapsing, in
+    /// order. Empty if the cluster has not been assigned a position in the document.
+    pub fn get_cluster_delimiters(&self, cluster_id: ClusterId) -> Vec<SmartString> {
+        if self.cluster_note_number(cluster_id).is_none() {
+            return Vec::new();
+        }
+        citeproc_proc::cluster_delimiters(self, cluster_id)
+    }
+
     /// Returns None if the cluster has not been assigned a position in the document.
     pub fn get_cluster(&self, cluster_id: ClusterId) -> Option<Arc<MarkupOutput>> {
         if self.cluster_note_number(cluster_id).is_some() {
@@ -561,6 +694,60 @@ impl Processor {
         }
     }
 
+    /// Renders a cluster and returns a copy of the IR tree it was built from, as an arena and its
+    /// root node. The tree reflects the cluster after disambiguation and cite collapsing, so it
+    /// can be inspected without recomputing anything.
+    pub fn render_cluster_with_ir(
+        &self,
+        cluster_id: ClusterId,
+    ) -> (SmartString, citeproc_proc::IrArena, citeproc_proc::NodeId) {
+        citeproc_proc::db::built_cluster_with_ir(self, cluster_id)
+    }
+
+    /// Renders a cluster with whatever disambiguation added to it wrapped in labelled markers,
+    /// e.g. `Smith 2000{ys:a}`, for showing which disambiguation features a style used.
+    pub fn render_cluster_disamb_debug(&self, cluster_id: ClusterId) -> SmartString {
+        citeproc_proc::db::built_cluster_disamb_debug(self, cluster_id)
+    }
+
+    /// The disambiguation passes that ran on each cite in a cluster, in the order they ran, for
+    /// working out why a cite came out the way it did. Cites are in their sorted order. Returns
+    /// None if the cluster has no position in the document.
+    pub fn get_cluster_disamb_passes(
+        &self,
+        cluster_id: ClusterId,
+    ) -> Option<Vec<Vec<citeproc_proc::DisambPass>>> {
+        let cites = self.cluster_cites_sorted(cluster_id)?;
+        let passes = cites
+            .iter()
+            .map(|&cite_id| self.ir_fully_disambiguated(cite_id).passes().to_vec())
+            .collect();
+        Some(passes)
+    }
+
+    /// A cite's intermediate representation after disambiguation as pretty-printed JSON, for
+    /// snapshot tests and bug reports. Each node lists its `type`, its `groupVars`, how it renders
+    /// (delimiters, affixes, display, rendered output) and its `children`.
+    pub fn debug_ir_json(&self, cite_id: CiteId) -> String {
+        let ir = citeproc_proc::db::cite_ir_json(self, cite_id);
+        serde_json::to_string_pretty(&ir).expect("IR serializes to JSON")
+    }
+
+    /// The edge stream of each cite in a cluster after disambiguation, in sorted order, for seeing
+    /// where year suffixes, locators and citation numbers fall in the output. Returns None if the
+    /// cluster has no position in the document.
+    pub fn get_cluster_edge_streams(
+        &self,
+        cluster_id: ClusterId,
+    ) -> Option<Vec<Vec<citeproc_proc::disamb::EdgeData>>> {
+        let cites = self.cluster_cites_sorted(cluster_id)?;
+        let streams = cites
+            .iter()
+            .map(|&cite_id| citeproc_proc::db::cite_edge_stream(self, cite_id))
+            .collect();
+        Some(streams)
+    }
+
     pub fn get_cluster_note_number(&self, cluster_id: ClusterId) -> Option<ClusterNumber> {
         self.cluster_note_number(cluster_id)
     }
@@ -575,6 +762,26 @@ impl Processor {
         self.bib_item(ref_id)
     }
 
+    /// The reference's bibliography sort keys as one string that compares the way the
+    /// bibliography is sorted. See [`citeproc_proc::sort_key_string`].
+    pub fn sort_key_string(&self, ref_id: Atom) -> Option<String> {
+        citeproc_proc::sort_key_string(self, ref_id)
+    }
+
+    /// For styles using `second-field-align`, returns a bibliography entry as `(left_field,
+    /// rest)`, so that hosts can lay out the two columns themselves. None if the style does not
+    /// use second-field-align, or the entry only has one field.
+    pub fn get_bib_item_split(&self, ref_id: Atom) -> Option<(MarkupOutput, MarkupOutput)> {
+        citeproc_proc::bib_item_split(self, ref_id)
+    }
+
+    /// Lists the commonly-required fields (author, title, container-title, publisher, issued)
+    /// that the style would render for this reference's type but the reference lacks, so hosts
+    /// can warn about incomplete data.
+    pub fn missing_required_fields(&self, ref_id: Atom) -> Vec<AnyVariable> {
+        citeproc_proc::missing_required_fields(self, ref_id)
+    }
+
     pub fn get_bibliography_meta(&self) -> Option<BibliographyMeta> {
         let style = self.get_style();
         style.bibliography.as_ref().map(|bib| {
@@ -599,26 +806,12 @@ impl Processor {
             return None;
         }
         let mut last_bibliography = self.last_bibliography.lock().unwrap();
-        let new = self.get_bibliography_map();
-        let old = std::mem::replace(&mut *last_bibliography, SavedBib::new());
-        let mut update = BibliographyUpdate::new();
-        for (k, v) in new.iter() {
-            let old_v = old.bib_entries.get(k);
-            if Some(v) != old_v {
-                update.updated_entries.insert(k.clone(), v.clone());
-            }
-        }
-        last_bibliography.bib_entries = new;
-        let sorted_refs = self.sorted_refs();
-        if sorted_refs.0 != old.sorted_refs.0 {
-            update.entry_ids = Some(sorted_refs.0.clone());
-        }
-        last_bibliography.sorted_refs = sorted_refs;
-        if update.updated_entries.is_empty() && update.entry_ids.is_none() {
-            None
-        } else {
-            Some(update)
-        }
+        let new = SavedBib {
+            sorted_refs: self.sorted_refs(),
+            bib_entries: self.get_bibliography_map(),
+        };
+        let old = std::mem::replace(&mut *last_bibliography, new);
+        diff_bibliography(&old, &last_bibliography)
     }
 
     pub fn all_clusters(&self) -> FnvHashMap<ClusterId, Arc<MarkupOutput>> {
@@ -667,6 +860,200 @@ impl Processor {
             .collect()
     }
 
+    /// Reports, for every cited reference, which disambiguation passes ran, which one resolved
+    /// it, the year suffix it was given and whether it remains ambiguous. Ordering is stable, so
+    /// the serialized report can be snapshot-tested.
+    pub fn disambiguation_report(&self) -> DisambReport {
+        let mut by_ref: FnvHashMap<Atom, RefDisambReport> = FnvHashMap::default();
+        for cluster in self.clusters_cites_sorted().iter() {
+            for &cite_id in cluster.cites.iter() {
+                let ref_id = cite_id.lookup(self).ref_id.clone();
+                let gen = self.ir_fully_disambiguated(cite_id);
+                let mut resolved_by = gen.resolved_by();
+                if resolved_by != Some(citeproc_proc::DisambPass::AddNames) {
+                    // Whether adding given names was enough is only worked out here, so the
+                    // disambiguation itself doesn't pay for an extra ambiguity test.
+                    let given_name_pass = gen
+                        .passes()
+                        .iter()
+                        .find(|pass| matches!(pass, citeproc_proc::DisambPass::AddGivenName(_)));
+                    if let Some(&pass) = given_name_pass {
+                        let matching = self.ir_gen2_matching_refs(cite_id);
+                        if matching.iter().all(|id| *id == ref_id) {
+                            resolved_by = Some(pass);
+                        }
+                    }
+                }
+                let entry = by_ref
+                    .entry(ref_id.clone())
+                    .or_insert_with(|| RefDisambReport {
+                        year_suffix: self
+                            .year_suffix_for(ref_id.clone())
+                            .map(|n| citeproc_io::utils::to_bijective_base_26(n).into()),
+                        id: ref_id,
+                        passes: Vec::new(),
+                        resolved_by: None,
+                        ambiguous: false,
+                    });
+                for kind in gen.passes().iter().map(DisambPassKind::from) {
+                    if !entry.passes.contains(&kind) {
+                        entry.passes.push(kind);
+                    }
+                }
+                let resolved_by = resolved_by.as_ref().map(DisambPassKind::from);
+                entry.resolved_by = entry.resolved_by.max(resolved_by);
+                entry.ambiguous |= gen.is_ambiguous();
+            }
+        }
+        let mut references: Vec<RefDisambReport> = by_ref.into_iter().map(|(_, v)| v).collect();
+        for refr in references.iter_mut() {
+            refr.passes.sort();
+            if refr.ambiguous {
+                refr.resolved_by = None;
+            }
+        }
+        references.sort_by(|a, b| a.id.cmp(&b.id));
+        DisambReport { references }
+    }
+
+    /// The year suffix each reference was given by disambiguation, as it would be rendered (`a`,
+    /// `b`, ..., `z`, `aa`, ...). References that did not need a suffix are left out.
+    ///
+    /// The suffixes are strings rather than `char`s, because after `z` they take two or more
+    /// letters.
+    pub fn all_year_suffixes(&self) -> FnvHashMap<Atom, SmartString> {
+        self.year_suffixes()
+            .iter()
+            .map(|(ref_id, &n)| {
+                let letters = citeproc_io::utils::to_bijective_base_26(n);
+                (ref_id.clone(), letters.into())
+            })
+            .collect()
+    }
+
+    /// Every cluster and the bibliography, with the warnings for rendering them.
+    pub fn full_render(&self) -> FullRender {
+        FullRender {
+            all_clusters: self.all_clusters(),
+            bib_entries: self.get_bibliography(),
+            warnings: self.warnings(),
+        }
+    }
+
+    /// Like `full_render`, but keyed by the string cluster ids.
+    pub fn full_render_str(&self) -> string_id::FullRender {
+        string_id::FullRender {
+            all_clusters: self.all_clusters_str(),
+            bib_entries: self.get_bibliography(),
+            warnings: self.warnings(),
+        }
+    }
+
+    /// Non-fatal problems that rendering the current document works around: terms missing from a
+    /// locale in use, macros that are never called and fields of rendered references that are
+    /// not CSL variables. Sorted and deduplicated.
+    ///
+    /// A locale is in use if it is the default locale, the bibliography's, one named by a
+    /// citation locale layout, or (with the `reference-locale` feature) a rendered reference's.
+    pub fn warnings(&self) -> Vec<Warning> {
+        let style = self.style();
+        let mut usage = StyleUsage::default();
+        let mut roots = vec![&style.citation.layout.elements[..]];
+        roots.extend(
+            style
+                .citation
+                .locale_layouts
+                .iter()
+                .map(|l| &l.elements[..]),
+        );
+        let mut sorts = vec![style.citation.sort.as_ref()];
+        if let Some(bib) = style.bibliography.as_ref() {
+            roots.push(&bib.layout.elements[..]);
+            sorts.push(bib.sort.as_ref());
+        }
+        if let Some(intext) = style.intext.as_ref() {
+            roots.push(&intext.layout.elements[..]);
+        }
+        for key in sorts
+            .into_iter()
+            .flatten()
+            .flat_map(|sort| sort.keys.iter())
+        {
+            if let SortSource::Macro(ref name) = key.sort_source {
+                usage.call_macro(&style, name);
+            }
+        }
+        for elements in roots {
+            usage.walk(&style, elements);
+        }
+
+        let mut rendered: Vec<Atom> = self.cited_keys().iter().cloned().collect();
+        if style.bibliography.is_some() {
+            rendered.extend(self.sorted_refs().0.iter().cloned());
+        }
+        rendered.sort();
+        rendered.dedup();
+        let references: Vec<Arc<Reference>> = rendered
+            .iter()
+            .filter_map(|id| self.reference(id.clone()))
+            .collect();
+
+        let mut locales: Vec<(Lang, Arc<Locale>)> =
+            vec![(self.default_lang(), self.default_locale())];
+        if style.bibliography.is_some() {
+            locales.push((self.bibliography_lang(), self.bibliography_locale()));
+        }
+        for lang in style
+            .citation
+            .locale_layouts
+            .iter()
+            .flat_map(|l| l.locale.iter())
+        {
+            locales.push((lang.clone(), self.merged_locale(lang.clone())));
+        }
+        if style.features.reference_locale {
+            for lang in references.iter().filter_map(|r| r.language.as_ref()) {
+                locales.push((lang.clone(), self.lang_locale(lang.clone())));
+            }
+        }
+        locales.sort_by(|a, b| a.0.cmp(&b.0));
+        locales.dedup_by(|a, b| a.0 == b.0);
+
+        let mut warnings: Vec<Warning> = Vec::new();
+        for (lang, locale) in locales.iter() {
+            warnings.extend(
+                usage
+                    .terms
+                    .iter()
+                    .filter(|&&sel| locale.get_text_term(sel, false).is_none())
+                    .map(|&sel| Warning::MissingTerm {
+                        term: term_name(sel),
+                        lang: lang.to_string().into(),
+                    }),
+            );
+        }
+        warnings.extend(
+            style
+                .macros
+                .keys()
+                .filter(|name| !usage.macros.contains(*name))
+                .map(|name| Warning::UnusedMacro { name: name.clone() }),
+        );
+        for refr in references.iter() {
+            warnings.extend(
+                refr.unknown_variables
+                    .iter()
+                    .map(|name| Warning::UnknownVariable {
+                        id: refr.id.clone(),
+                        name: name.as_str().into(),
+                    }),
+            );
+        }
+        warnings.sort();
+        warnings.dedup();
+        warnings
+    }
+
     pub fn get_reference(&self, ref_id: Atom) -> Option<Arc<Reference>> {
         self.reference(ref_id)
     }
@@ -684,9 +1071,38 @@ impl Processor {
         self.set_locale_input_langs(Arc::new(langs));
     }
 
+    /// Replaces terms in every locale with the terms in `overrides`, without editing the style.
+    /// An override only replaces the term of the same name and form (long, short, symbol, ...),
+    /// and brings its own singular and plural. Anything but terms in `overrides` is ignored.
+    ///
+    /// Parse `overrides` with [`Locale::parse`] from a `<locale>` holding only a `<terms>` block.
+    /// Each call replaces the previous overrides.
+    pub fn set_term_overrides(&mut self, overrides: Locale) {
+        self.set_term_overrides_with_durability(Arc::new(overrides), Durability::HIGH);
+    }
+
+    /// Overrides the style's `near-note-distance` (default 5): how many notes back the last cite
+    /// of a reference can be for a later cite to be `near-note` rather than `far-note`. Pass
+    /// `None` to go back to the style's value.
+    pub fn set_near_note_distance(&mut self, distance: Option<u32>) {
+        self.set_near_note_distance_override_with_durability(distance, Durability::HIGH);
+    }
+
     pub fn get_langs_in_use(&self) -> Vec<Lang> {
         let dl = self.default_lang();
-        let mut vec: Vec<Lang> = dl.iter_fetchable_langs().collect();
+        let bl = self.bibliography_lang();
+        let mut vec: Vec<Lang> = dl
+            .iter_fetchable_langs()
+            .chain(bl.iter_fetchable_langs())
+            .collect();
+        if self.style().features.reference_locale {
+            for ref_id in self.all_keys().iter() {
+                let refr = self.reference(ref_id.clone());
+                if let Some(lang) = refr.as_ref().and_then(|r| r.language.as_ref()) {
+                    vec.extend(lang.iter_fetchable_langs());
+                }
+            }
+        }
         vec.sort();
         vec.dedup();
         vec
@@ -839,6 +1255,31 @@ impl Processor {
         citeproc_proc::bib_item_preview(self, preview_ref_id.clone(), arc.as_ref(), &formatter)
     }
 
+    /// Renders a single cluster against an ad-hoc set of references, using a throwaway
+    /// processor with the bundled en-US locale. Useful for testing styles without touching the
+    /// state of a long-lived processor. Disambiguation happens among `refs` as usual.
+    pub fn render_cluster_with_refs(
+        cites: Vec<Cite<Markup>>,
+        refs: Vec<Reference>,
+        style: &str,
+        locale_override: Option<Lang>,
+    ) -> Result<SmartString, StyleError> {
+        let mut db = Processor::new(InitOptions {
+            style,
+            locale_override,
+            ..Default::default()
+        })?;
+        db.reset_references(refs);
+        let id = db.cluster_id("sandbox");
+        db.insert_cluster(Cluster::new(id, cites, None));
+        db.set_cluster_order(&[ClusterPosition::note(id, 1)])
+            .expect("a single cluster position is always a valid ordering");
+        Ok(db
+            .get_cluster(id)
+            .map(|built| (*built).clone())
+            .unwrap_or_default())
+    }
+
     fn preview_marked_init<'a>(
         &mut self,
         positions: &[ClusterPosition],
@@ -999,3 +1440,62 @@ impl Processor {
         Ok(())
     }
 }
+
+/// The terms and macros a style actually reaches from its layouts and sort keys.
+#[derive(Default)]
+struct StyleUsage {
+    terms: Vec<TextTermSelector>,
+    macros: FnvHashSet<SmartString>,
+}
+
+impl StyleUsage {
+    fn call_macro(&mut self, style: &Style, name: &SmartString) {
+        // Only walk each macro once; this also stops at recursive macros.
+        if self.macros.insert(name.clone()) {
+            if let Some(elements) = style.macros.get(name) {
+                self.walk(style, elements);
+            }
+        }
+    }
+
+    fn walk(&mut self, style: &Style, elements: &[Element]) {
+        for el in elements {
+            match el {
+                Element::Text(text) => match text.source {
+                    TextSource::Macro(ref name) => self.call_macro(style, name),
+                    TextSource::Term(sel, _) => self.terms.push(sel),
+                    _ => {}
+                },
+                Element::Group(group) => self.walk(style, &group.elements),
+                Element::Choose(choose) => {
+                    let csl::Choose(head, rest, last) = &**choose;
+                    for branch in std::iter::once(head).chain(rest.iter()) {
+                        self.walk(style, &branch.1);
+                    }
+                    self.walk(style, &last.0);
+                }
+                Element::Names(names) => {
+                    if let Some(substitute) = names.substitute.as_ref() {
+                        self.walk(style, &substitute.0);
+                    }
+                }
+                Element::Label(_) | Element::Number(_) | Element::Date(_) => {}
+            }
+        }
+    }
+}
+
+fn term_name(sel: TextTermSelector) -> SmartString {
+    use csl::{GenderedTermSelector, SimpleTermSelector};
+    let name: &str = match sel {
+        TextTermSelector::Simple(SimpleTermSelector::Misc(ref term, _)) => term.as_ref(),
+        TextTermSelector::Simple(SimpleTermSelector::Category(ref cat, _)) => cat.as_ref(),
+        TextTermSelector::Simple(SimpleTermSelector::Quote(ref term)) => term.as_ref(),
+        TextTermSelector::Gendered(GenderedTermSelector::Number(ref var, _)) => var.as_ref(),
+        TextTermSelector::Gendered(GenderedTermSelector::Locator(ref loc, _)) => loc.as_ref(),
+        TextTermSelector::Gendered(GenderedTermSelector::Month(ref month, _)) => month.as_ref(),
+        TextTermSelector::Gendered(GenderedTermSelector::Season(ref season, _)) => season.as_ref(),
+        TextTermSelector::Role(ref role) => role.0.as_ref(),
+    };
+    name.into()
+}
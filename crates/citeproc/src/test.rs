@@ -20,24 +20,73 @@ macro_rules! assert_cluster {
     };
 }
 
-fn test_db(style: Option<&str>) -> Processor {
+fn init_logger() {
     static INIT_ONCE: Once = Once::new();
     INIT_ONCE.call_once(|| {
         env_logger::init();
     });
-    Processor::new(InitOptions {
-        style: style.unwrap_or(
+}
+
+fn test_db(style: Option<&str>) -> Processor {
+    test_db_with_format(
+        style.unwrap_or(
             r#"<style version="1.0" class="in-text">
                                     <citation><layout></layout></citation>
                                   </style>"#,
         ),
-        format: SupportedFormat::Plain,
+        SupportedFormat::Plain,
+    )
+}
+
+fn test_db_with_format(style: &str, format: SupportedFormat) -> Processor {
+    init_logger();
+    Processor::new(InitOptions {
+        style,
+        format,
         test_mode: true,
         ..Default::default()
     })
     .unwrap()
 }
 
+/// Like `test_db`, but fetching `locales`. `options` gives the style and anything else that
+/// isn't a default.
+fn test_db_with_locales(
+    options: InitOptions,
+    locales: impl IntoIterator<Item = (Lang, String)>,
+) -> Processor {
+    init_logger();
+    Processor::new(InitOptions {
+        format: SupportedFormat::Plain,
+        test_mode: true,
+        fetcher: Some(Arc::new(PredefinedLocales(locales.into_iter().collect()))),
+        ..options
+    })
+    .unwrap()
+}
+
+/// A locale for `lang` whose body (style options, terms) is `extra_xml`.
+fn locale_xml(lang: &str, extra_xml: &str) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+        <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="{}">{}</locale>"#,
+        lang, extra_xml
+    )
+}
+
+fn insert_json_ref(db: &mut Processor, json: &str) {
+    db.insert_reference(serde_json::from_str(json).unwrap());
+}
+
+/// A book by one author, "J. <family>", issued in `year`.
+fn insert_ref(db: &mut Processor, id: &str, family: &str, year: i32) {
+    let json = format!(
+        r#"{{ "id": "{}", "type": "book", "author": [{{ "family": "{}", "given": "J" }}], "issued": {{ "date-parts": [[{}]] }} }}"#,
+        id, family, year
+    );
+    insert_json_ref(db, &json);
+}
+
 fn insert_basic_refs(db: &mut Processor, ref_ids: &[&str]) {
     for &id in ref_ids {
         let mut refr = Reference::empty(Atom::from(id), CslType::Book);
@@ -163,6 +212,116 @@ mod position {
         );
     }
 
+    #[test]
+    fn cite_positions_ibid_with_locator() {
+        let mut db = test_db(None);
+        let cites = [
+            r#"{ "id": "one", "locator": "5" }"#,
+            r#"{ "id": "one", "locator": "5" }"#,
+            r#"{ "id": "one", "locator": "7" }"#,
+            r#"{ "id": "one" }"#,
+            r#"{ "id": "one", "locator": "3" }"#,
+        ];
+        let ids: Vec<ClusterId> = (1..=cites.len() as u32).map(|n| cid(&mut db, n)).collect();
+        db.init_clusters(
+            ids.iter()
+                .zip(cites.iter())
+                .map(|(&id, json)| Cluster {
+                    id,
+                    cites: vec![serde_json::from_str(json).unwrap()],
+                    mode: None,
+                })
+                .collect(),
+        );
+        let order: Vec<_> = ids
+            .iter()
+            .zip(1..)
+            .map(|(&id, note)| ClusterPosition::note(id, note))
+            .collect();
+        db.set_cluster_order(&order).unwrap();
+        let poss = db.cite_positions();
+        let position = |id: ClusterId| poss[&db.cluster_cites(id.raw())[0]].0;
+        assert_eq!(position(ids[0]), Position::First);
+        // same locator
+        assert_eq!(position(ids[1]), Position::IbidNear);
+        // different locator
+        assert_eq!(position(ids[2]), Position::IbidWithLocatorNear);
+        // dropping the locator is not ibid
+        assert_eq!(position(ids[3]), Position::NearNote);
+        // adding one is
+        assert_eq!(position(ids[4]), Position::IbidWithLocatorNear);
+    }
+
+    #[test]
+    fn cite_positions_across_notes() {
+        let mut db = test_db(None);
+        let notes = [
+            (1, "one"),
+            (2, "one"),
+            (3, "other"),
+            (4, "one"),
+            (10, "one"),
+            (11, "other"),
+        ];
+        let ids: Vec<ClusterId> = (1..=notes.len() as u32).map(|n| cid(&mut db, n)).collect();
+        db.init_clusters(
+            ids.iter()
+                .zip(notes.iter())
+                .map(|(&id, &(_, ref_id))| Cluster {
+                    id,
+                    cites: vec![Cite::basic(ref_id)],
+                    mode: None,
+                })
+                .collect(),
+        );
+        let order: Vec<_> = ids
+            .iter()
+            .zip(notes.iter())
+            .map(|(&id, &(note, _))| ClusterPosition::note(id, note))
+            .collect();
+        db.set_cluster_order(&order).unwrap();
+        let poss = db.cite_positions();
+        let position = |id: ClusterId| poss[&db.cluster_cites(id.raw())[0]];
+        assert_eq!(position(ids[0]), (Position::First, None));
+        // consecutive
+        assert_eq!(position(ids[1]), (Position::IbidNear, Some(1)));
+        assert_eq!(position(ids[2]), (Position::First, None));
+        // an intervening cite to another reference
+        assert_eq!(position(ids[3]), (Position::NearNote, Some(1)));
+        // still ibid when the notes in between cite nothing, but no longer near
+        assert_eq!(position(ids[4]), (Position::Ibid, Some(1)));
+        // more than near-note-distance (5) notes after note 3
+        assert_eq!(position(ids[5]), (Position::FarNote, Some(3)));
+    }
+
+    #[test]
+    fn cite_positions_near_note_distance() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one", "other"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let three = cid(&mut db, 3);
+        db.init_clusters(vec![
+            Cluster::new(one, vec![Cite::basic("one")], None),
+            Cluster::new(two, vec![Cite::basic("other")], None),
+            Cluster::new(three, vec![Cite::basic("one")], None),
+        ]);
+        db.set_cluster_order(&[
+            ClusterPosition::note(one, 1),
+            ClusterPosition::note(two, 2),
+            ClusterPosition::note(three, 4),
+        ])
+        .unwrap();
+        let id3 = db.cluster_cites(three.raw())[0];
+        assert_eq!(db.cite_positions()[&id3], (Position::NearNote, Some(1)));
+        db.set_near_note_distance(Some(2));
+        assert_eq!(db.cite_positions()[&id3], (Position::FarNote, Some(1)));
+        db.set_near_note_distance(Some(3));
+        assert_eq!(db.cite_positions()[&id3], (Position::NearNote, Some(1)));
+        db.set_near_note_distance(None);
+        assert_eq!(db.cite_positions()[&id3], (Position::NearNote, Some(1)));
+    }
+
     #[test]
     fn cite_positions_near_note() {
         let mut db = test_db(None);
@@ -178,6 +337,62 @@ mod position {
         assert_eq!(poss[&id2], (Position::First, None));
         assert_eq!(poss[&id3], (Position::NearNote, Some(1)));
     }
+
+    #[test]
+    fn cite_positions_intra_note_after_two_refs() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one", "other"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        db.init_clusters(vec![
+            Cluster::new(one, vec![Cite::basic("one"), Cite::basic("other")], None),
+            Cluster::new(two, vec![Cite::basic("other")], None),
+        ]);
+        let order = [ClusterPosition::note(one, 1), ClusterPosition::note(two, 1)];
+        db.set_cluster_order(&order).unwrap();
+        let poss = db.cite_positions();
+        let id = db.cluster_cites(two.raw())[0];
+        // The preceding citation also cites "one", so this is subsequent, not ibid.
+        assert_eq!(poss[&id], (Position::NearNote, Some(1)));
+    }
+
+    #[test]
+    fn cite_positions_intra_note_ibid() {
+        let mut db = test_db(None);
+        insert_basic_refs(&mut db, &["one", "other"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let three = cid(&mut db, 3);
+        db.init_clusters(vec![
+            Cluster {
+                id: one,
+                cites: vec![Cite::basic("other")],
+                mode: None,
+            },
+            Cluster {
+                id: two,
+                cites: vec![Cite::basic("one")],
+                mode: None,
+            },
+            Cluster {
+                id: three,
+                cites: vec![Cite::basic("one")],
+                mode: None,
+            },
+        ]);
+        db.set_cluster_order(&[
+            ClusterPosition::note(one, 1),
+            ClusterPosition::note(two, 1),
+            ClusterPosition::note(three, 1),
+        ])
+        .unwrap();
+        let poss = db.cite_positions();
+        let id2 = db.cluster_cites(two.raw())[0];
+        let id3 = db.cluster_cites(three.raw())[0];
+        assert_eq!(poss[&id2], (Position::First, None));
+        // The note also cites "other", but the immediately preceding cite is "one".
+        assert_eq!(poss[&id3], (Position::IbidNear, Some(1)));
+    }
 }
 
 mod preview {
@@ -416,4 +631,1804 @@ mod terms {
             Some("USA"),
         )
     }
+
+    #[test]
+    fn term_overrides() {
+        let mut db = test_db(Some(
+            r#"<style class="in-text" version="1.0.1">
+            <citation>
+                <layout>
+                    <group delimiter=" ">
+                        <text variable="title" />
+                        <text term="and" />
+                        <text term="editor" form="short" plural="true" />
+                        <text term="editor" />
+                    </group>
+                </layout>
+            </citation>
+        </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("Book one and eds. editor"));
+        let overrides = terms(
+            r#"<term name="and">&amp;</term>
+            <term name="editor" form="short"><single>Ed.</single><multiple>Eds.</multiple></term>"#,
+        );
+        db.set_term_overrides(Locale::parse(&overrides).unwrap());
+        // The long form of editor is not overridden
+        assert_cluster!(db.get_cluster(one), Some("Book one & Eds. editor"));
+    }
+
+    #[test]
+    fn term_dialect_fallback_chain() {
+        let de_at = Lang::Iso(IsoLang::Deutsch, Some(IsoCountry::AT));
+        let de_de = Lang::Iso(IsoLang::Deutsch, Some(IsoCountry::DE));
+        let db = Processor::safe_default(Arc::new(predefined_xml(&[
+            (
+                Lang::en_us(),
+                r#"<term name="and">and</term><term name="circa">circa</term>"#,
+            ),
+            (
+                de_de,
+                r#"<term name="and">und</term><term name="ibid">ebd.</term>"#,
+            ),
+            (de_at.clone(), r#"<term name="ibid">ebenda</term>"#),
+        ])));
+        // de-AT -> de-DE -> en-US, with the most specific definition winning
+        let locale = db.merged_locale(de_at);
+        let term = |term| {
+            let sel = SimpleTermSelector::Misc(term, TermFormExtended::Long);
+            locale.get_text_term(TextTermSelector::Simple(sel), false)
+        };
+        assert_eq!(term(MiscTerm::Ibid), Some("ebenda"));
+        assert_eq!(term(MiscTerm::And), Some("und"));
+        assert_eq!(term(MiscTerm::Circa), Some("circa"));
+    }
+}
+
+mod disamb_report {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation disambiguate-add-year-suffix="true">
+            <layout delimiter="; ">
+                <group delimiter=" ">
+                    <names variable="author"><name form="short" /></names>
+                    <date variable="issued"><date-part name="year" /></date>
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn mk_db(style: &str) -> Processor {
+        let mut db = test_db(Some(style));
+        insert_ref(&mut db, "a", "Smith", 2000);
+        insert_ref(&mut db, "b", "Smith", 2000);
+        insert_ref(&mut db, "c", "Jones", 2001);
+        insert_ascending_notes(&mut db, &["a", "b", "c"]);
+        db
+    }
+
+    fn entry(
+        id: &str,
+        passes: Vec<DisambPassKind>,
+        resolved_by: Option<DisambPassKind>,
+        suffix: Option<&str>,
+        ambiguous: bool,
+    ) -> RefDisambReport {
+        RefDisambReport {
+            id: Atom::from(id),
+            passes,
+            year_suffix: suffix.map(SmartString::from),
+            resolved_by,
+            ambiguous,
+        }
+    }
+
+    #[test]
+    fn report_year_suffixes() {
+        let db = mk_db(STYLE);
+        let report = db.disambiguation_report();
+        assert_eq!(
+            report.references,
+            vec![
+                entry(
+                    "a",
+                    vec![DisambPassKind::AddYearSuffix],
+                    Some(DisambPassKind::AddYearSuffix),
+                    Some("a"),
+                    false
+                ),
+                entry(
+                    "b",
+                    vec![DisambPassKind::AddYearSuffix],
+                    Some(DisambPassKind::AddYearSuffix),
+                    Some("b"),
+                    false
+                ),
+                entry("c", vec![], None, None, false),
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&report.references[0]).unwrap(),
+            r#"{"id":"a","passes":["addYearSuffix"],"yearSuffix":"a","resolvedBy":"addYearSuffix","ambiguous":false}"#
+        );
+    }
+
+    #[test]
+    fn all_year_suffixes() {
+        let mut db = test_db(Some(STYLE));
+        insert_ref(&mut db, "a", "Smith", 2000);
+        insert_ref(&mut db, "b", "Smith", 2000);
+        insert_ref(&mut db, "c", "Smith", 2000);
+        insert_ref(&mut db, "d", "Jones", 2001);
+        insert_ascending_notes(&mut db, &["a", "b", "c", "d"]);
+        let suffixes = db.all_year_suffixes();
+        let mut suffixes: Vec<_> = suffixes
+            .iter()
+            .map(|(id, ys)| (&**id, ys.as_str()))
+            .collect();
+        suffixes.sort();
+        assert_eq!(suffixes, vec![("a", "a"), ("b", "b"), ("c", "c")]);
+    }
+
+    #[test]
+    fn debug_render_marks_year_suffix() {
+        let mut db = mk_db(STYLE);
+        let (one, three) = (cid(&mut db, 1), cid(&mut db, 3));
+        assert_eq!(db.render_cluster_disamb_debug(one), "Smith 2000{ys:a}");
+        assert_eq!(db.render_cluster_disamb_debug(three), "Jones 2001");
+        assert_cluster!(db.get_cluster(one), Some("Smith 2000a"));
+    }
+
+    #[test]
+    fn cluster_disamb_passes() {
+        use citeproc_proc::DisambPass;
+        let mut db = mk_db(STYLE);
+        let (one, two, three) = (cid(&mut db, 1), cid(&mut db, 2), cid(&mut db, 3));
+        assert_eq!(
+            db.get_cluster_disamb_passes(one),
+            Some(vec![vec![DisambPass::AddYearSuffix(1)]])
+        );
+        assert_eq!(
+            db.get_cluster_disamb_passes(two),
+            Some(vec![vec![DisambPass::AddYearSuffix(2)]])
+        );
+        assert_eq!(db.get_cluster_disamb_passes(three), Some(vec![vec![]]));
+        assert_cluster!(db.get_cluster(two), Some("Smith 2000b"));
+    }
+
+    #[test]
+    fn cluster_edge_streams() {
+        use citeproc_proc::disamb::EdgeData;
+        let mut db = mk_db(STYLE);
+        let (one, three) = (cid(&mut db, 1), cid(&mut db, 3));
+        let output = |stream: &[EdgeData]| {
+            stream
+                .iter()
+                .filter_map(|edge| match edge {
+                    EdgeData::Output(s) => Some(s.as_str()),
+                    _ => None,
+                })
+                .collect::<String>()
+        };
+        let streams = db.get_cluster_edge_streams(one).unwrap();
+        assert_eq!(streams.len(), 1);
+        assert_eq!(output(&streams[0]), "Smith 2000");
+        assert_eq!(streams[0].last(), Some(&EdgeData::YearSuffix));
+        let streams = db.get_cluster_edge_streams(three).unwrap();
+        assert_eq!(output(&streams[0]), "Jones 2001");
+        assert!(!streams[0].contains(&EdgeData::YearSuffix));
+    }
+
+    #[test]
+    fn debug_ir_json() {
+        let mut db = mk_db(STYLE);
+        let one = cid(&mut db, 1);
+        let cite_id = db.cluster_cites_sorted(one).unwrap()[0];
+        let json: serde_json::Value = serde_json::from_str(&db.debug_ir_json(cite_id)).unwrap();
+        assert_eq!(json["type"], "Seq");
+        assert_eq!(json["isLayout"], true);
+        assert!(json["groupVars"].is_string());
+        let group = &json["children"][0];
+        assert_eq!(group["type"], "Seq");
+        assert_eq!(group["delimiter"], " ");
+        assert_eq!(group["groupVars"], "Important");
+        assert!(group["children"].is_array());
+    }
+
+    #[test]
+    fn report_remaining_ambiguity() {
+        let style = STYLE.replace(r#" disambiguate-add-year-suffix="true""#, "");
+        let db = mk_db(&style);
+        let report = db.disambiguation_report();
+        assert_eq!(
+            report.references,
+            vec![
+                entry("a", vec![], None, None, true),
+                entry("b", vec![], None, None, true),
+                entry("c", vec![], None, None, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn report_pass_order() {
+        let style = STYLE.replace(
+            r#"<citation "#,
+            r#"<citation disambiguate-add-names="true" disambiguate-add-givenname="true" "#,
+        );
+        let mut db = mk_db(&style);
+        let json = r#"{ "id": "d", "type": "book", "author": [{ "family": "Smith", "given": "Adam" }], "issued": { "date-parts": [[2000]] } }"#;
+        insert_json_ref(&mut db, json);
+        insert_ascending_notes(&mut db, &["a", "b", "c", "d"]);
+        let report = db.disambiguation_report();
+        let names = vec![DisambPassKind::AddNames, DisambPassKind::AddGivenName];
+        let mut all = names.clone();
+        all.push(DisambPassKind::AddYearSuffix);
+        assert_eq!(
+            report.references,
+            vec![
+                // Both are "J Smith", so only a year suffix tells them apart.
+                entry(
+                    "a",
+                    all.clone(),
+                    Some(DisambPassKind::AddYearSuffix),
+                    Some("a"),
+                    false
+                ),
+                entry(
+                    "b",
+                    all,
+                    Some(DisambPassKind::AddYearSuffix),
+                    Some("b"),
+                    false
+                ),
+                entry("c", vec![], None, None, false),
+                entry("d", names, Some(DisambPassKind::AddGivenName), None, false),
+            ]
+        );
+    }
+}
+
+mod append_cite {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="note" version="1.0.1">
+        <citation>
+            <layout delimiter="; ">
+                <text variable="title" />
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    #[test]
+    fn append_cite_leaves_other_clusters_cached() {
+        let mut db = test_db(Some(STYLE));
+        insert_basic_refs(&mut db, &["one", "two"]);
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        let id1 = db.cluster_cites(one.raw())[0];
+        let id2 = db.cluster_cites(two.raw())[0];
+        let gen1 = db.ir_fully_disambiguated(id1);
+        let gen2 = db.ir_fully_disambiguated(id2);
+        db.append_cite_to_cluster(two, Cite::basic("one"));
+        assert_cluster!(db.get_cluster(two), Some("Book two; Book one"));
+        assert_cluster!(db.get_cluster(one), Some("Book one"));
+        // The existing cites were not re-interned, and nothing they depend on changed, so
+        // neither was rendered again.
+        assert_eq!(db.cluster_cites(two.raw())[0], id2);
+        assert!(Arc::ptr_eq(&gen1, &db.ir_fully_disambiguated(id1)));
+        assert!(Arc::ptr_eq(&gen2, &db.ir_fully_disambiguated(id2)));
+    }
+
+    #[test]
+    fn append_cite_to_new_cluster() {
+        let mut db = test_db(Some(STYLE));
+        insert_basic_refs(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        db.append_cite_to_cluster(one, Cite::basic("one"));
+        let order = [ClusterPosition::note(one, 1)];
+        db.set_cluster_order(&order).unwrap();
+        assert_cluster!(db.get_cluster(one), Some("Book one"));
+    }
+}
+
+mod bib_split {
+    use super::*;
+
+    fn style(align: &str) -> String {
+        style_with_layout(
+            align,
+            r#"<text variable="citation-number" prefix="[" suffix="]" />
+                <text variable="title" />"#,
+        )
+    }
+
+    fn style_with_layout(align: &str, layout: &str) -> String {
+        format!(
+            r##"
+    <style class="in-text" version="1.0.1">
+        <citation><layout><text variable="title" /></layout></citation>
+        <bibliography{}>
+            <layout>
+                {}
+            </layout>
+        </bibliography>
+    </style>
+"##,
+            align, layout
+        )
+    }
+
+    fn mk_db(align: &str) -> Processor {
+        let mut db = test_db(Some(&style(align)));
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        db
+    }
+
+    #[test]
+    fn split_flush() {
+        let db = mk_db(r#" second-field-align="flush""#);
+        let (left, rest) = db.get_bib_item_split(Atom::from("one")).unwrap();
+        assert_eq!(left, "[1]");
+        assert_eq!(rest, "Book one");
+        // The same split as the rendered entry.
+        assert_eq!(
+            format!("{}{}", left, rest),
+            db.get_bib_item(Atom::from("one")).as_str()
+        );
+    }
+
+    #[test]
+    fn split_margin() {
+        let db = mk_db(r#" second-field-align="margin""#);
+        let (left, rest) = db.get_bib_item_split(Atom::from("one")).unwrap();
+        assert_eq!(left, "[1]");
+        assert_eq!(rest, "Book one");
+    }
+
+    #[test]
+    fn no_split_without_second_field_align() {
+        let db = mk_db("");
+        assert_eq!(db.get_bib_item_split(Atom::from("one")), None);
+    }
+
+    fn split_with_layout(layout: &str) -> Option<(SmartString, SmartString)> {
+        let mut db = test_db(Some(&style_with_layout(
+            r#" second-field-align="flush""#,
+            layout,
+        )));
+        let json = r#"{ "id": "one", "type": "book", "title": "Book one", "author": [{ "family": "Smith", "given": "John" }] }"#;
+        insert_json_ref(&mut db, json);
+        insert_ascending_notes(&mut db, &["one"]);
+        db.get_bib_item_split(Atom::from("one"))
+    }
+
+    #[test]
+    fn split_leading_names() {
+        let (left, rest) = split_with_layout(
+            r#"<names variable="author" />
+                <group><text variable="title" /></group>"#,
+        )
+        .unwrap();
+        assert_eq!(left, "John Smith");
+        assert_eq!(rest, "Book one");
+    }
+
+    #[test]
+    fn split_inside_single_group() {
+        let (left, rest) = split_with_layout(
+            r#"<group delimiter=" ">
+                    <names variable="author" />
+                    <text variable="title" />
+                </group>"#,
+        )
+        .unwrap();
+        assert_eq!(left, "John Smith");
+        assert_eq!(rest, "Book one");
+    }
+
+    #[test]
+    fn split_after_subsequent_author_substitute() {
+        let style = style_with_layout(
+            r#" second-field-align="flush" subsequent-author-substitute="---""#,
+            r#"<names variable="author" />
+                <group><text variable="title" /></group>"#,
+        );
+        let mut db = test_db(Some(&style));
+        for (id, title) in &[("one", "Book one"), ("two", "Book two")] {
+            let json = format!(
+                r#"{{ "id": "{}", "type": "book", "title": "{}", "author": [{{ "family": "Smith", "given": "John" }}] }}"#,
+                id, title
+            );
+            insert_json_ref(&mut db, &json);
+        }
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let (left, rest) = db.get_bib_item_split(Atom::from("two")).unwrap();
+        assert_eq!(left, "---");
+        assert_eq!(rest, "Book two");
+    }
+}
+
+mod bib_locale {
+    use super::*;
+
+    fn locale(lang: &str, and: &str, piq: bool) -> String {
+        let extra = format!(
+            r#"<style-options punctuation-in-quote="{}" />
+            <terms><term name="and">{}</term></terms>"#,
+            piq, and
+        );
+        locale_xml(lang, &extra)
+    }
+
+    fn de_de() -> Lang {
+        Lang::Iso(IsoLang::Deutsch, Some(IsoCountry::DE))
+    }
+
+    fn mk_db(bibliography_locale_override: Option<Lang>) -> Processor {
+        mk_db_with_style(
+            r#"
+    <style class="in-text" version="1.0.1" default-locale="en-US">
+        <citation>
+            <layout>
+                <group delimiter=" ">
+                    <text variable="title" />
+                    <text term="and" />
+                </group>
+            </layout>
+        </citation>
+        <bibliography>
+            <layout>
+                <group delimiter=" ">
+                    <text variable="title" />
+                    <text term="and" />
+                </group>
+            </layout>
+        </bibliography>
+    </style>"#,
+            bibliography_locale_override,
+        )
+    }
+
+    fn mk_db_with_style(style: &str, bibliography_locale_override: Option<Lang>) -> Processor {
+        let mut db = test_db_with_locales(
+            InitOptions {
+                style,
+                bibliography_locale_override,
+                ..Default::default()
+            },
+            vec![
+                (Lang::en_us(), locale("en-US", "and", true)),
+                (de_de(), locale("de-DE", "und", false)),
+            ],
+        );
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        db
+    }
+
+    #[test]
+    fn same_locale_by_default() {
+        let mut db = mk_db(None);
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("Book one and"));
+        assert_eq!(db.get_bib_item(Atom::from("one")).as_str(), "Book one and");
+    }
+
+    #[test]
+    fn bibliography_in_other_locale() {
+        let mut db = mk_db(Some(de_de()));
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("Book one and"));
+        assert_eq!(db.get_bib_item(Atom::from("one")).as_str(), "Book one und");
+        let langs = db.get_langs_in_use();
+        assert!(langs.contains(&Lang::en_us()));
+        assert!(langs.contains(&de_de()));
+    }
+
+    #[test]
+    fn bibliography_punctuation_in_quote() {
+        let style = r#"
+    <style class="in-text" version="1.0.1" default-locale="en-US">
+        <citation>
+            <layout><text variable="title" quotes="true" suffix="." /></layout>
+        </citation>
+        <bibliography>
+            <layout><text variable="title" quotes="true" suffix="." /></layout>
+        </bibliography>
+    </style>"#;
+        let mut db = mk_db_with_style(style, Some(de_de()));
+        let one = cid(&mut db, 1);
+        // en-US moves the period inside the quotes, de-DE does not.
+        let cluster = db.get_cluster(one).unwrap();
+        assert!(!cluster.ends_with('.'), "{}", cluster);
+        let bib_item = db.get_bib_item(Atom::from("one"));
+        assert!(bib_item.ends_with('.'), "{}", bib_item);
+    }
+}
+
+mod reference_locale {
+    use super::*;
+
+    fn locale(lang: &str, and: &str, june: &str) -> String {
+        let extra = format!(
+            r#"<terms>
+                <term name="and">{}</term>
+                <term name="month-06">{}</term>
+            </terms>"#,
+            and, june
+        );
+        locale_xml(lang, &extra)
+    }
+
+    fn fr_fr() -> Lang {
+        Lang::Iso(IsoLang::French, Some(IsoCountry::FR))
+    }
+
+    const STYLE: &'static str = r#"
+    <style class="in-text" version="1.0.1" default-locale="en-US">
+        <features><feature name="reference-locale" /></features>
+        <citation>
+            <layout>
+                <group delimiter=", ">
+                    <names variable="author"><name and="text" /></names>
+                    <date variable="issued">
+                        <date-part name="month" suffix=" " />
+                        <date-part name="year" />
+                    </date>
+                </group>
+            </layout>
+        </citation>
+    </style>"#;
+
+    fn mk_db(style: &str) -> Processor {
+        let mut db = test_db_with_locales(
+            InitOptions {
+                style,
+                ..Default::default()
+            },
+            vec![
+                (Lang::en_us(), locale("en-US", "and", "June")),
+                (fr_fr(), locale("fr-FR", "et", "juin")),
+            ],
+        );
+        let refs = &[
+            ("fr", "fr-FR", "Dupont", "Martin"),
+            ("en", "en-US", "Smith", "Jones"),
+        ];
+        for (id, lang, one, two) in refs {
+            let json = format!(
+                r#"{{ "id": "{}", "type": "book", "language": "{}",
+                    "author": [{{ "family": "{}" }}, {{ "family": "{}" }}],
+                    "issued": {{ "date-parts": [[2020, 6]] }} }}"#,
+                id, lang, one, two
+            );
+            insert_json_ref(&mut db, &json);
+        }
+        insert_ascending_notes(&mut db, &["fr", "en"]);
+        db
+    }
+
+    #[test]
+    fn each_reference_in_its_own_locale() {
+        let mut db = mk_db(STYLE);
+        let (one, two) = (cid(&mut db, 1), cid(&mut db, 2));
+        assert_cluster!(db.get_cluster(one), Some("Dupont et Martin, juin 2020"));
+        assert_cluster!(db.get_cluster(two), Some("Smith and Jones, June 2020"));
+        assert!(db.get_langs_in_use().contains(&fr_fr()));
+    }
+
+    #[test]
+    fn default_locale_without_feature() {
+        let style = STYLE.replace(
+            r#"<features><feature name="reference-locale" /></features>"#,
+            "",
+        );
+        let mut db = mk_db(&style);
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("Dupont and Martin, June 2020"));
+        assert!(!db.get_langs_in_use().contains(&fr_fr()));
+    }
+}
+
+mod gendered_ordinals {
+    use super::*;
+
+    const FR_FR: &'static str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="fr-FR">
+        <terms>
+            <term name="ordinal">e</term>
+            <term name="ordinal-01" gender-form="feminine" match="whole-number">re</term>
+            <term name="ordinal-01" gender-form="masculine" match="whole-number">er</term>
+            <term name="month-01" gender="masculine">janvier</term>
+            <term name="edition" gender="feminine">
+                <single>édition</single>
+                <multiple>éditions</multiple>
+            </term>
+        </terms></locale>"#;
+
+    #[test]
+    fn french_day_and_edition() {
+        let style = r#"
+    <style class="in-text" version="1.0.1" default-locale="fr-FR">
+        <citation>
+            <layout>
+                <group delimiter=", ">
+                    <date variable="issued">
+                        <date-part name="day" form="ordinal" suffix=" " />
+                        <date-part name="month" suffix=" " />
+                        <date-part name="year" />
+                    </date>
+                    <number variable="edition" form="ordinal" />
+                </group>
+            </layout>
+        </citation>
+    </style>"#;
+        let mut db = test_db_with_locales(
+            InitOptions {
+                style,
+                ..Default::default()
+            },
+            vec![(
+                Lang::Iso(IsoLang::French, Some(IsoCountry::FR)),
+                FR_FR.to_string(),
+            )],
+        );
+        for (id, day) in &[("one", 1), ("two", 2)] {
+            let json = format!(
+                r#"{{ "id": "{}", "type": "book", "edition": "{}",
+                    "issued": {{ "date-parts": [[2020, 1, {}]] }} }}"#,
+                id, day, day
+            );
+            insert_json_ref(&mut db, &json);
+        }
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let (one, two) = (cid(&mut db, 1), cid(&mut db, 2));
+        // The day takes the month's gender, the edition the gender of its term. Numbers without
+        // a gendered ordinal fall back to the neutral one.
+        assert_cluster!(db.get_cluster(one), Some("1er janvier 2020, 1re"));
+        assert_cluster!(db.get_cluster(two), Some("2e janvier 2020, 2e"));
+    }
+}
+
+mod citation_number_affixes {
+    use super::*;
+
+    #[test]
+    fn cite_and_bib_affixes_differ() {
+        let mut db = test_db(Some(
+            r#"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <text variable="citation-number" prefix="[" suffix="]" />
+            </layout>
+        </citation>
+        <bibliography>
+            <layout>
+                <group delimiter=" ">
+                    <text variable="citation-number" suffix="." />
+                    <text variable="title" />
+                </group>
+            </layout>
+        </bibliography>
+    </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one", "two"]);
+        insert_ascending_notes(&mut db, &["one", "two"]);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        assert_cluster!(db.get_cluster(one), Some("[1]"));
+        assert_cluster!(db.get_cluster(two), Some("[2]"));
+        assert_eq!(db.get_bib_item(Atom::from("one")).as_str(), "1. Book one");
+        assert_eq!(db.get_bib_item(Atom::from("two")).as_str(), "2. Book two");
+    }
+}
+
+mod would_collapse_change {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout delimiter="; ">
+                <group delimiter=" ">
+                    <names variable="author"><name form="short" /></names>
+                    <date variable="issued"><date-part name="year" /></date>
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn mk_db() -> Processor {
+        let mut db = test_db(Some(STYLE));
+        insert_ref(&mut db, "smith1", "Smith", 2000);
+        insert_ref(&mut db, "smith2", "Smith", 2001);
+        insert_ref(&mut db, "jones", "Jones", 2002);
+        let one = cid(&mut db, 1);
+        let two = cid(&mut db, 2);
+        db.init_clusters(vec![
+            Cluster::new(one, vec![Cite::basic("smith1"), Cite::basic("jones")], None),
+            Cluster::new(
+                two,
+                vec![Cite::basic("smith1"), Cite::basic("smith2")],
+                None,
+            ),
+        ]);
+        let order = [ClusterPosition::note(one, 1), ClusterPosition::note(two, 2)];
+        db.set_cluster_order(&order).unwrap();
+        db
+    }
+
+    #[test]
+    fn no_shared_authors() {
+        let mut db = mk_db();
+        let one = cid(&mut db, 1);
+        assert!(!db.would_collapse_change(one, Collapse::Year));
+        assert_cluster!(db.get_cluster(one), Some("Smith 2000; Jones 2002"));
+    }
+
+    #[test]
+    fn shared_authors() {
+        let mut db = mk_db();
+        let two = cid(&mut db, 2);
+        assert!(db.would_collapse_change(two, Collapse::Year));
+        // The style itself does not collapse, so the output is unchanged.
+        assert_cluster!(db.get_cluster(two), Some("Smith 2000; Smith 2001"));
+    }
+}
+
+mod cluster_delimiters {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation collapse="year" after-collapse-delimiter="; ">
+            <layout delimiter=", ">
+                <group delimiter=" ">
+                    <names variable="author"><name form="short" /></names>
+                    <date variable="issued"><date-part name="year" /></date>
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    #[test]
+    fn after_collapse() {
+        let mut db = test_db(Some(STYLE));
+        insert_ref(&mut db, "smith1", "Smith", 2000);
+        insert_ref(&mut db, "smith2", "Smith", 2001);
+        insert_ref(&mut db, "jones", "Jones", 2002);
+        let one = cid(&mut db, 1);
+        db.init_clusters(vec![Cluster::new(
+            one,
+            vec![
+                Cite::basic("smith1"),
+                Cite::basic("smith2"),
+                Cite::basic("jones"),
+            ],
+            None,
+        )]);
+        let order = [ClusterPosition::note(one, 1)];
+        db.set_cluster_order(&order).unwrap();
+        assert_cluster!(db.get_cluster(one), Some("Smith 2000, 2001; Jones 2002"));
+        let delims = db.get_cluster_delimiters(one);
+        assert_eq!(
+            delims.iter().map(SmartString::as_str).collect::<Vec<_>>(),
+            vec![", ", "; "]
+        );
+    }
+
+    #[test]
+    fn unpositioned_cluster() {
+        let mut db = test_db(Some(STYLE));
+        let one = cid(&mut db, 1);
+        assert!(db.get_cluster_delimiters(one).is_empty());
+    }
+}
+
+mod sandbox {
+    use super::*;
+
+    #[test]
+    fn ambiguous_adhoc_refs() {
+        let style = r##"
+    <style class="in-text" version="1.0.1">
+        <info><id>id</id><title /></info>
+        <citation disambiguate-add-year-suffix="true">
+            <layout delimiter="; ">
+                <group delimiter=" ">
+                    <names variable="author"><name form="short" /></names>
+                    <date variable="issued"><date-part name="year" /></date>
+                </group>
+            </layout>
+        </citation>
+    </style>"##;
+        let refr = |id: &str| -> Reference {
+            let json = format!(
+                r#"{{ "id": "{}", "type": "book", "author": [{{ "family": "Smith", "given": "J" }}], "issued": {{ "date-parts": [[2000]] }} }}"#,
+                id
+            );
+            serde_json::from_str(&json).unwrap()
+        };
+        let rendered = Processor::render_cluster_with_refs(
+            vec![Cite::basic("a"), Cite::basic("b")],
+            vec![refr("a"), refr("b")],
+            style,
+            None,
+        )
+        .unwrap();
+        assert_eq!(rendered, "Smith 2000a; Smith 2000b");
+    }
+}
+
+mod plain_text {
+    use super::*;
+
+    #[test]
+    fn strips_formatting_keeps_quotes_and_case() {
+        let mut db = test_db(Some(
+            r#"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <group font-weight="bold" delimiter=", ">
+                    <text variable="title" font-style="italic" quotes="true" text-case="uppercase" />
+                    <text value="plain" font-variant="small-caps" />
+                </group>
+            </layout>
+        </citation>
+    </style>"#,
+        ));
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("\u{201C}BOOK ONE\u{201D}, plain"));
+    }
+}
+
+mod locale_date_form {
+    use super::*;
+
+    const FR_FR: &'static str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <locale xmlns="http://purl.org/net/xbiblio/csl" version="1.0" xml:lang="fr-FR">
+            <terms><term name="month-01">janvier</term></terms>
+            <date form="text">
+                <date-part name="day" suffix=" " />
+                <date-part name="month" suffix=" " />
+                <date-part name="year" />
+            </date>
+        </locale>"#;
+
+    fn render(default_locale: &str) -> Option<Arc<SmartString>> {
+        let fr_fr = (
+            Lang::Iso(IsoLang::French, Some(IsoCountry::FR)),
+            FR_FR.to_owned(),
+        );
+        let style = format!(
+            r#"<style class="in-text" version="1.0.1" default-locale="{}">
+                <citation><layout><date variable="issued" form="text" /></layout></citation>
+            </style>"#,
+            default_locale
+        );
+        let mut db = test_db_with_locales(
+            InitOptions {
+                style: &style,
+                ..Default::default()
+            },
+            PredefinedLocales::bundled_en_us()
+                .0
+                .into_iter()
+                .chain(Some(fr_fr)),
+        );
+        let json = r#"{ "id": "one", "type": "book", "issued": { "date-parts": [[2019, 1, 5]] } }"#;
+        insert_json_ref(&mut db, json);
+        insert_ascending_notes(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        db.get_cluster(one)
+    }
+
+    #[test]
+    fn english_text_date() {
+        assert_cluster!(render("en-US"), Some("January 5, 2019"));
+    }
+
+    #[test]
+    fn french_text_date() {
+        assert_cluster!(render("fr-FR"), Some("5 janvier 2019"));
+    }
+}
+
+mod output_formats {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation>
+            <layout>
+                <group delimiter=", ">
+                    <names variable="author"><name form="short" font-variant="small-caps" /></names>
+                    <text variable="title" font-style="italic" />
+                    <text variable="publisher" font-weight="bold" />
+                </group>
+            </layout>
+        </citation>
+        <bibliography>
+            <layout suffix=".">
+                <group delimiter=" ">
+                    <text variable="title" font-style="italic" />
+                    <text variable="publisher" font-weight="bold" />
+                </group>
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    /// Escaping and formatting of each writer are unit tested in its own module; this only
+    /// checks that the processor routes cites and bibliography entries through them.
+    fn render(format: SupportedFormat) -> (Option<Arc<SmartString>>, Arc<SmartString>) {
+        let mut db = test_db_with_format(STYLE, format);
+        let json = r#"{ "id": "one", "type": "book", "author": [{ "family": "Star*" }], "title": "R&D_2 #1", "publisher": "Smith @ Sons" }"#;
+        insert_json_ref(&mut db, json);
+        insert_ascending_notes(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        let bib = db.get_bib_item(Atom::from("one"));
+        (db.get_cluster(one), bib)
+    }
+
+    #[test]
+    fn latex() {
+        let (cite, bib) = render(SupportedFormat::Latex);
+        assert_cluster!(
+            cite,
+            Some(r"\textsc{Star*}, \emph{R\&D\_2 \#1}, \textbf{Smith @ Sons}")
+        );
+        assert_eq!(bib.as_str(), r"\emph{R\&D\_2 \#1} \textbf{Smith @ Sons}.");
+    }
+
+    #[test]
+    fn typst() {
+        let (cite, bib) = render(SupportedFormat::Typst);
+        assert_cluster!(
+            cite,
+            Some(r"#smallcaps[Star\*], #emph[R&D\_2 \#1], #strong[Smith \@ Sons]")
+        );
+        assert_eq!(bib.as_str(), r"#emph[R&D\_2 \#1] #strong[Smith \@ Sons].");
+    }
+
+    #[test]
+    fn asciidoc() {
+        let (cite, bib) = render(SupportedFormat::AsciiDoc);
+        assert_cluster!(
+            cite,
+            Some("[.smallcaps]#Star{asterisk}#, __R&Dpass:[_]2 pass:[#]1__, **Smith @ Sons**")
+        );
+        assert_eq!(bib.as_str(), "__R&Dpass:[_]2 pass:[#]1__ **Smith @ Sons**.");
+    }
+}
+
+mod warnings {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <macro name="title"><text variable="title" /></macro>
+        <macro name="unused"><text variable="title" /></macro>
+        <citation>
+            <layout>
+                <group delimiter=" ">
+                    <text term="author" />
+                    <text macro="title" />
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    #[test]
+    fn missing_term_warns_but_renders() {
+        let mut db = test_db(Some(STYLE));
+        insert_basic_refs(&mut db, &["one"]);
+        insert_ascending_notes(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        let render = db.full_render();
+        assert_cluster!(render.all_clusters.get(&one).cloned(), Some("Book one"));
+        assert_eq!(
+            render.warnings,
+            vec![
+                Warning::MissingTerm {
+                    term: "author".into(),
+                    lang: "en-US".into(),
+                },
+                Warning::UnusedMacro {
+                    name: "unused".into()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn no_warnings() {
+        let db = test_db(Some(
+            &STYLE.replace(r#"<text term="author" />"#, r#"<text macro="unused" />"#),
+        ));
+        assert_eq!(db.warnings(), vec![]);
+    }
+
+    #[test]
+    fn unknown_variable_warns_but_renders() {
+        let style = STYLE.replace(r#"<text term="author" />"#, r#"<text macro="unused" />"#);
+        let mut db = test_db(Some(&style));
+        insert_json_ref(
+            &mut db,
+            r#"{ "id": "one", "type": "book", "title": "One", "titel": "Typo" }"#,
+        );
+        // Not rendered, so not reported
+        insert_json_ref(
+            &mut db,
+            r#"{ "id": "two", "type": "book", "title": "Two", "titel": "Typo" }"#,
+        );
+        insert_ascending_notes(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        assert_cluster!(db.get_cluster(one), Some("One One"));
+        assert_eq!(
+            db.warnings(),
+            vec![Warning::UnknownVariable {
+                id: "one".into(),
+                name: "titel".into(),
+            }]
+        );
+    }
+
+    fn de_de() -> Lang {
+        Lang::Iso(IsoLang::Deutsch, Some(IsoCountry::DE))
+    }
+
+    /// fr-FR defines the `and` term, de-DE does not.
+    fn db_in_french(style: &str, bibliography_locale_override: Option<Lang>) -> Processor {
+        let fr_fr = Lang::Iso(IsoLang::French, Some(IsoCountry::FR));
+        test_db_with_locales(
+            InitOptions {
+                style,
+                bibliography_locale_override,
+                ..Default::default()
+            },
+            vec![
+                (
+                    fr_fr,
+                    locale_xml("fr-FR", r#"<terms><term name="and">et</term></terms>"#),
+                ),
+                (de_de(), locale_xml("de-DE", "")),
+            ],
+        )
+    }
+
+    #[test]
+    fn missing_term_in_bibliography_locale() {
+        let style = r#"
+    <style class="in-text" version="1.0.1" default-locale="fr-FR">
+        <citation><layout><text term="and" /></layout></citation>
+        <bibliography><layout><text term="and" /></layout></bibliography>
+    </style>"#;
+        let db = db_in_french(style, Some(de_de()));
+        assert_eq!(
+            db.warnings(),
+            vec![Warning::MissingTerm {
+                term: "and".into(),
+                lang: "de-DE".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn missing_term_in_reference_locale() {
+        let style = r#"
+    <style class="in-text" version="1.0.1" default-locale="fr-FR">
+        <features><feature name="reference-locale" /></features>
+        <citation><layout><text term="and" /></layout></citation>
+    </style>"#;
+        let mut db = db_in_french(style, None);
+        assert_eq!(db.warnings(), vec![]);
+        insert_json_ref(
+            &mut db,
+            r#"{ "id": "one", "type": "book", "title": "Eins", "language": "de-DE" }"#,
+        );
+        insert_ascending_notes(&mut db, &["one"]);
+        assert_eq!(
+            db.warnings(),
+            vec![Warning::MissingTerm {
+                term: "and".into(),
+                lang: "de-DE".into(),
+            }]
+        );
+    }
+}
+
+mod year_suffix_base26 {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation disambiguate-add-year-suffix="true">
+            <layout>
+                <group delimiter=" ">
+                    <names variable="author"><name form="short" /></names>
+                    <date variable="issued"><date-part name="year" /></date>
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    #[test]
+    fn suffixes_past_z() {
+        let mut db = test_db(Some(STYLE));
+        let ids: Vec<String> = (1..=28).map(|n| format!("r{}", n)).collect();
+        for id in &ids {
+            let json = format!(
+                r#"{{ "id": "{}", "type": "book", "author": [{{ "family": "Smith" }}], "issued": {{ "date-parts": [[2000]] }} }}"#,
+                id
+            );
+            insert_json_ref(&mut db, &json);
+        }
+        let ids: Vec<&str> = ids.iter().map(|x| x.as_str()).collect();
+        insert_ascending_notes(&mut db, &ids);
+        let n1 = cid(&mut db, 1);
+        let n26 = cid(&mut db, 26);
+        let n27 = cid(&mut db, 27);
+        let n28 = cid(&mut db, 28);
+        assert_cluster!(db.get_cluster(n1), Some("Smith 2000a"));
+        assert_cluster!(db.get_cluster(n26), Some("Smith 2000z"));
+        assert_cluster!(db.get_cluster(n27), Some("Smith 2000aa"));
+        assert_cluster!(db.get_cluster(n28), Some("Smith 2000ab"));
+    }
+}
+
+mod add_names_limit {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation et-al-min="3" et-al-use-first="1" disambiguate-add-names="true">
+            <layout delimiter="; ">
+                <names variable="author" />
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn insert_ref(db: &mut Processor, id: &str, third: &str) {
+        let json = format!(
+            r#"{{ "id": "{}", "type": "book", "author": [{{ "family": "Smith" }}, {{ "family": "Jones" }}, {{ "family": "{}" }}, {{ "family": "Doe" }}] }}"#,
+            id, third
+        );
+        insert_json_ref(db, &json);
+    }
+
+    fn mk_db() -> Processor {
+        let mut db = test_db(Some(STYLE));
+        insert_ref(&mut db, "a", "Brown");
+        insert_ref(&mut db, "b", "White");
+        let one = cid(&mut db, 1);
+        db.init_clusters(vec![Cluster::new(
+            one,
+            vec![Cite::basic("a"), Cite::basic("b")],
+            None,
+        )]);
+        let order = [ClusterPosition::note(one, 1)];
+        db.set_cluster_order(&order).unwrap();
+        db
+    }
+
+    #[test]
+    fn unlimited() {
+        let mut db = mk_db();
+        let one = cid(&mut db, 1);
+        assert_cluster!(
+            db.get_cluster(one),
+            Some("Smith, Jones, Brown, et al.; Smith, Jones, White, et al.")
+        );
+    }
+
+    #[test]
+    fn limited() {
+        let mut db = mk_db();
+        db.limit_add_names(Some(1));
+        let one = cid(&mut db, 1);
+        // Adding one name does not help, so the cites roll back and stay ambiguous.
+        assert_cluster!(db.get_cluster(one), Some("Smith et al.; Smith et al."));
+        db.limit_add_names(None);
+        assert_cluster!(
+            db.get_cluster(one),
+            Some("Smith, Jones, Brown, et al.; Smith, Jones, White, et al.")
+        );
+    }
+}
+
+mod locator_term_precedence {
+    use super::*;
+
+    fn en_au() -> Lang {
+        Lang::Iso(IsoLang::English, Some(IsoCountry::AU))
+    }
+
+    fn render(style_terms: &str, en_au_terms: &str) -> Option<Arc<SmartString>> {
+        let locales = vec![
+            (
+                Lang::en_us(),
+                locale_xml(
+                    "en-US",
+                    r#"<terms><term name="page" form="short">USA</term></terms>"#,
+                ),
+            ),
+            (
+                en_au(),
+                locale_xml("en-AU", &format!("<terms>{}</terms>", en_au_terms)),
+            ),
+        ];
+        let style = format!(
+            r#"<style class="in-text" version="1.0.1" default-locale="en-AU">
+                <locale><terms>{}</terms></locale>
+                <citation><layout>
+                    <group delimiter=" ">
+                        <label variable="locator" form="short" />
+                        <text variable="locator" />
+                    </group>
+                </layout></citation>
+            </style>"#,
+            style_terms
+        );
+        let mut db = test_db_with_locales(
+            InitOptions {
+                style: &style,
+                ..Default::default()
+            },
+            locales,
+        );
+        insert_basic_refs(&mut db, &["one"]);
+        let one = cid(&mut db, 1);
+        let cite = serde_json::from_str(r#"{ "id": "one", "locator": "5", "label": "page" }"#);
+        db.init_clusters(vec![Cluster::new(one, vec![cite.unwrap()], None)]);
+        let order = [ClusterPosition::note(one, 1)];
+        db.set_cluster_order(&order).unwrap();
+        db.get_cluster(one)
+    }
+
+    const AU_PAGE: &'static str = r#"<term name="page" form="short">AU</term>"#;
+
+    #[test]
+    fn style_term_beats_locale() {
+        assert_cluster!(
+            render(r#"<term name="page" form="short">at</term>"#, AU_PAGE),
+            Some("at 5")
+        );
+    }
+
+    #[test]
+    fn locale_beats_parent_locale() {
+        assert_cluster!(render("", AU_PAGE), Some("AU 5"));
+    }
+
+    #[test]
+    fn parent_locale_fallback() {
+        assert_cluster!(render("", ""), Some("USA 5"));
+    }
+}
+
+mod render_with_ir {
+    use super::*;
+    use citeproc_proc::IR;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation collapse="citation-number">
+            <layout delimiter=", " prefix="[" suffix="]">
+                <text variable="citation-number" />
+            </layout>
+        </citation>
+        <bibliography><layout><text variable="title" /></layout></bibliography>
+    </style>
+"##;
+
+    #[test]
+    fn ir_matches_collapsed_output() {
+        let mut db = test_db(Some(STYLE));
+        insert_basic_refs(&mut db, &["a", "b", "c", "d"]);
+        let one = cid(&mut db, 1);
+        let cites = ["a", "b", "c"].iter().map(|&id| Cite::basic(id)).collect();
+        db.init_clusters(vec![Cluster::new(one, cites, None)]);
+        let order = [ClusterPosition::note(one, 1)];
+        db.set_cluster_order(&order).unwrap();
+        let (string, arena, root) = db.render_cluster_with_ir(one);
+        assert_eq!(string.as_str(), "[1–3]");
+        assert_cluster!(db.get_cluster(one), Some("[1–3]"));
+        match &arena.get(root).unwrap().get().0 {
+            IR::Seq(seq) => assert!(seq.is_layout),
+            other => panic!("expected a layout seq, got {}", other),
+        }
+        // The middle of the collapsed range is not written anywhere, so it is left out.
+        assert_eq!(root.children(&arena).count(), 2);
+    }
+}
+
+mod missing_fields {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <macro name="author">
+            <names variable="author">
+                <substitute>
+                    <names variable="editor" />
+                </substitute>
+            </names>
+        </macro>
+        <citation>
+            <layout>
+                <group delimiter=", ">
+                    <text macro="author" />
+                    <text variable="title" />
+                    <choose>
+                        <if type="book">
+                            <text variable="publisher" />
+                        </if>
+                        <else-if type="article-journal">
+                            <text variable="container-title" />
+                        </else-if>
+                    </choose>
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn missing(json: &str) -> Vec<AnyVariable> {
+        let mut db = test_db(Some(STYLE));
+        insert_json_ref(&mut db, json);
+        db.missing_required_fields(Atom::from("ITEM-1"))
+    }
+
+    #[test]
+    fn book_missing_publisher() {
+        let json = r#"{
+            "id": "ITEM-1", "type": "book", "title": "Title",
+            "author": [{ "family": "Smith", "given": "John" }]
+        }"#;
+        assert_eq!(
+            missing(json),
+            vec![AnyVariable::Ordinary(Variable::Publisher)]
+        );
+    }
+
+    #[test]
+    fn only_branches_for_the_type() {
+        let json = r#"{
+            "id": "ITEM-1", "type": "article-journal", "title": "Title",
+            "author": [{ "family": "Smith", "given": "John" }]
+        }"#;
+        assert_eq!(
+            missing(json),
+            vec![AnyVariable::Ordinary(Variable::ContainerTitle)]
+        );
+    }
+
+    #[test]
+    fn substitute_satisfies_names() {
+        let json = r#"{
+            "id": "ITEM-1", "type": "book", "title": "Title", "publisher": "Publisher",
+            "editor": [{ "family": "Smith", "given": "John" }]
+        }"#;
+        assert!(missing(json).is_empty());
+    }
+}
+
+mod ibid_name_expansion {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation disambiguate-add-givenname="true" givenname-disambiguation-rule="by-cite">
+            <layout>
+                <group delimiter=" ">
+                    <names variable="author"><name form="short" /></names>
+                    <choose>
+                        <if position="ibid"><text value="ibid" /></if>
+                    </choose>
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn insert_ref(db: &mut Processor, id: &str, given: &str) {
+        let json = format!(
+            r#"{{ "id": "{}", "type": "book", "author": [{{ "family": "Smith", "given": "{}" }}] }}"#,
+            id, given
+        );
+        insert_json_ref(db, &json);
+    }
+
+    #[test]
+    fn ibid_does_not_expand_given_names() {
+        let mut db = test_db(Some(STYLE));
+        insert_ref(&mut db, "jane", "Jane");
+        insert_ref(&mut db, "john", "John");
+        insert_ascending_notes(&mut db, &["jane", "john", "john"]);
+        let (one, two, three) = (cid(&mut db, 1), cid(&mut db, 2), cid(&mut db, 3));
+        assert_cluster!(db.get_cluster(one), Some("Jane Smith"));
+        assert_cluster!(db.get_cluster(two), Some("John Smith"));
+        assert_cluster!(db.get_cluster(three), Some("Smith ibid"));
+    }
+
+    #[test]
+    fn subsequent_still_expands_given_names() {
+        let mut db = test_db(Some(STYLE));
+        insert_ref(&mut db, "jane", "Jane");
+        insert_ref(&mut db, "john", "John");
+        insert_ascending_notes(&mut db, &["jane", "john", "jane"]);
+        let (one, two, three) = (cid(&mut db, 1), cid(&mut db, 2), cid(&mut db, 3));
+        assert_cluster!(db.get_cluster(one), Some("Jane Smith"));
+        assert_cluster!(db.get_cluster(two), Some("John Smith"));
+        assert_cluster!(db.get_cluster(three), Some("Jane Smith"));
+    }
+
+    #[test]
+    fn ibid_keeps_year_suffix() {
+        let style = r##"
+        <style class="in-text" version="1.0.1">
+            <citation disambiguate-add-givenname="true" disambiguate-add-year-suffix="true">
+                <layout>
+                    <group delimiter=" ">
+                        <names variable="author"><name form="short" /></names>
+                        <date variable="issued"><date-part name="year" /></date>
+                        <choose>
+                            <if position="ibid"><text value="ibid" /></if>
+                        </choose>
+                    </group>
+                </layout>
+            </citation>
+        </style>
+    "##;
+        let mut db = test_db(Some(style));
+        for id in &["a", "b"] {
+            let json = format!(
+                r#"{{ "id": "{}", "type": "book", "author": [{{ "family": "Smith", "given": "J" }}], "issued": {{ "date-parts": [[2000]] }} }}"#,
+                id
+            );
+            insert_json_ref(&mut db, &json);
+        }
+        insert_ascending_notes(&mut db, &["a", "b", "b"]);
+        let (one, two, three) = (cid(&mut db, 1), cid(&mut db, 2), cid(&mut db, 3));
+        assert_cluster!(db.get_cluster(one), Some("Smith 2000a"));
+        assert_cluster!(db.get_cluster(two), Some("Smith 2000b"));
+        assert_cluster!(db.get_cluster(three), Some("Smith 2000b ibid"));
+    }
+}
+
+mod reference_flags {
+    use super::*;
+    use fnv::FnvHashSet;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <features>
+            <feature name="reference-flags" />
+        </features>
+        <citation>
+            <layout>
+                <group delimiter=" ">
+                    <text variable="title" />
+                    <choose>
+                        <if flag="peer-reviewed"><text value="(reviewed)" /></if>
+                    </choose>
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    fn flags(list: &[&str]) -> FnvHashSet<String> {
+        list.iter().map(|&s| String::from(s)).collect()
+    }
+
+    #[test]
+    fn branch_on_flag() {
+        let mut db = test_db(Some(STYLE));
+        insert_basic_refs(&mut db, &["a", "b"]);
+        db.set_reference_flags(Atom::from("a"), flags(&["peer-reviewed"]));
+        insert_ascending_notes(&mut db, &["a", "b"]);
+        let (one, two) = (cid(&mut db, 1), cid(&mut db, 2));
+        assert_cluster!(db.get_cluster(one), Some("Book a (reviewed)"));
+        assert_cluster!(db.get_cluster(two), Some("Book b"));
+
+        db.set_reference_flags(Atom::from("a"), flags(&[]));
+        db.set_reference_flags(Atom::from("b"), flags(&["peer-reviewed", "other"]));
+        assert_cluster!(db.get_cluster(one), Some("Book a"));
+        assert_cluster!(db.get_cluster(two), Some("Book b (reviewed)"));
+    }
+
+    #[test]
+    fn flag_needs_feature() {
+        let style = STYLE.replace(r#"<feature name="reference-flags" />"#, "");
+        assert!(Style::parse(&style).is_err());
+    }
+}
+
+mod incremental_bib {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation><layout><text variable="title" /></layout></citation>
+        <bibliography subsequent-author-substitute="---">
+            <sort><key variable="issued" /></sort>
+            <layout>
+                <group delimiter=" ">
+                    <names variable="author"><name form="short" /></names>
+                    <date variable="issued"><date-part name="year" /></date>
+                </group>
+            </layout>
+        </bibliography>
+    </style>
+"##;
+
+    fn smith(year: i32) -> Reference {
+        let json = format!(
+            r#"{{ "id": "s{}", "type": "book", "author": [{{ "family": "Smith" }}], "issued": {{ "date-parts": [[{}]] }} }}"#,
+            year, year
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    fn updated(update: &BibliographyUpdate) -> Vec<(&str, &str)> {
+        let mut entries: Vec<_> = update
+            .updated_entries
+            .iter()
+            .map(|(k, v)| (k.as_ref(), v.as_str()))
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn insert_between_same_author() {
+        let mut db = test_db(Some(STYLE));
+        db.include_uncited(IncludeUncited::All);
+        db.insert_reference(smith(1999));
+        db.insert_reference(smith(2001));
+        let update = db.insert_reference_with_bib_update(smith(2000)).unwrap();
+        // The neighbours keep their substitute state, so only the new entry is re-sent.
+        assert_eq!(updated(&update), vec![("s2000", "--- 2000")]);
+        assert_eq!(
+            update.entry_ids,
+            Some(vec![
+                Atom::from("s1999"),
+                Atom::from("s2000"),
+                Atom::from("s2001")
+            ])
+        );
+        let update = db.insert_reference_with_bib_update(smith(1998)).unwrap();
+        assert_eq!(
+            updated(&update),
+            vec![("s1998", "Smith 1998"), ("s1999", "--- 1999")]
+        );
+    }
+}
+
+mod sort_key_string {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation><layout><text variable="title" /></layout></citation>
+        <bibliography>
+            <sort>
+                <key variable="author" />
+                <key variable="volume" />
+                <key variable="issued" />
+            </sort>
+            <layout><text variable="title" /></layout>
+        </bibliography>
+    </style>
+"##;
+
+    fn insert_ref(db: &mut Processor, id: &str, family: &str, volume: u32, year: i32) {
+        let json = format!(
+            r#"{{ "id": "{}", "type": "book", "author": [{{ "family": "{}" }}], "volume": {}, "issued": {{ "date-parts": [[{}]] }} }}"#,
+            id, family, volume, year
+        );
+        insert_json_ref(db, &json);
+    }
+
+    #[test]
+    fn key_strings_sort_like_bibliography() {
+        let mut db = test_db(Some(STYLE));
+        db.include_uncited(IncludeUncited::All);
+        insert_ref(&mut db, "smith-10", "Smith", 10, 2000);
+        insert_ref(&mut db, "smith-9", "Smith", 9, 2005);
+        insert_ref(&mut db, "adams", "Adams", 20, 1990);
+        insert_ref(&mut db, "smith-9-early", "Smith", 9, 1999);
+        let bib_order: Vec<Atom> = db.get_bibliography().into_iter().map(|e| e.id).collect();
+        assert_eq!(
+            bib_order,
+            vec![
+                Atom::from("adams"),
+                Atom::from("smith-9-early"),
+                Atom::from("smith-9"),
+                Atom::from("smith-10"),
+            ]
+        );
+        let mut by_key = bib_order.clone();
+        by_key.sort_by_key(|id| db.sort_key_string(id.clone()).unwrap());
+        assert_eq!(by_key, bib_order);
+    }
+
+    #[test]
+    fn no_bibliography_sort() {
+        let mut db = test_db(None);
+        insert_ref(&mut db, "adams", "Adams", 1, 2000);
+        assert_eq!(db.sort_key_string(Atom::from("adams")), None);
+    }
+}
+
+mod bibliography_meta {
+    use super::*;
+
+    #[test]
+    fn layout_attributes() {
+        let db = test_db(Some(
+            r##"
+    <style class="in-text" version="1.0.1">
+        <citation><layout><text variable="title" /></layout></citation>
+        <bibliography hanging-indent="true" line-spacing="2" entry-spacing="0" second-field-align="flush">
+            <layout><text variable="title" /></layout>
+        </bibliography>
+    </style>
+"##,
+        ));
+        let meta = db.get_bibliography_meta().unwrap();
+        assert!(meta.hanging_indent);
+        assert_eq!(meta.line_spacing, 2);
+        assert_eq!(meta.entry_spacing, 0);
+        assert!(matches!(
+            meta.second_field_align,
+            Some(crate::SecondFieldAlign::Flush)
+        ));
+    }
+
+    #[test]
+    fn defaults() {
+        let db = test_db(Some(
+            r##"
+    <style class="in-text" version="1.0.1">
+        <citation><layout><text variable="title" /></layout></citation>
+        <bibliography><layout><text variable="title" /></layout></bibliography>
+    </style>
+"##,
+        ));
+        let meta = db.get_bibliography_meta().unwrap();
+        assert!(!meta.hanging_indent);
+        assert_eq!(meta.line_spacing, 1);
+        assert_eq!(meta.entry_spacing, 1);
+        assert!(meta.second_field_align.is_none());
+    }
+
+    #[test]
+    fn no_bibliography() {
+        let db = test_db(None);
+        assert!(db.get_bibliography_meta().is_none());
+    }
+}
+
+mod disamb_groups {
+    use super::*;
+
+    const STYLE: &'static str = r##"
+    <style class="in-text" version="1.0.1">
+        <citation disambiguate-add-givenname="true" disambiguate-add-year-suffix="true">
+            <layout delimiter="; ">
+                <group delimiter=" ">
+                    <names variable="author">
+                        <name form="short" initialize-with=". " />
+                    </names>
+                    <date variable="issued" form="text" date-parts="year" />
+                </group>
+            </layout>
+        </citation>
+    </style>
+"##;
+
+    const REFS: &[(&str, &str, &str, i32)] = &[
+        ("smith-a", "Smith", "John", 2000),
+        ("jones-a", "Jones", "Anne", 2001),
+        ("brown", "Brown", "Carol", 2002),
+        ("smith-b", "Smith", "John", 2000),
+        ("jones-b", "Jones", "Bob", 2001),
+    ];
+
+    fn mk_db() -> Processor {
+        let mut db = test_db(Some(STYLE));
+        for &(id, family, given, year) in REFS {
+            let json = format!(
+                r#"{{ "id": "{}", "type": "book", "author": [{{ "family": "{}", "given": "{}" }}], "issued": {{ "date-parts": [[{}]] }} }}"#,
+                id, family, given, year
+            );
+            insert_json_ref(&mut db, &json);
+        }
+        let ids: Vec<&str> = REFS.iter().map(|r| r.0).collect();
+        insert_ascending_notes(&mut db, &ids);
+        db
+    }
+
+    #[test]
+    fn groups_by_shared_names() {
+        let db = mk_db();
+        let groups: Vec<Vec<Atom>> = db
+            .disamb_groups()
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|id| id.lookup(&db).ref_id.clone())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            vec![
+                vec![Atom::from("smith-a"), Atom::from("smith-b")],
+                vec![Atom::from("jones-a"), Atom::from("jones-b")],
+                vec![Atom::from("brown")],
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_matches_sequential() {
+        // compute() disambiguates the groups on worker threads
+        let db = mk_db();
+        let mut parallel: Vec<(SmartString, String)> = db
+            .batched_updates()
+            .clusters
+            .into_iter()
+            .map(|(id, built)| (db.lookup_cluster_id(id).unwrap(), built.to_string()))
+            .collect();
+        parallel.sort();
+
+        let mut fresh = mk_db();
+        let sequential: Vec<(SmartString, String)> = (1..=REFS.len() as u32)
+            .map(|n| {
+                let id = cid(&mut fresh, n);
+                (n.to_string().into(), fresh.built_cluster(id).to_string())
+            })
+            .collect();
+        assert_eq!(parallel, sequential);
+    }
+
+    #[test]
+    fn disambiguates_within_groups() {
+        let mut db = mk_db();
+        let mut text = |n: u32| {
+            let id = cid(&mut db, n);
+            db.built_cluster(id).to_string()
+        };
+        assert!(text(1).ends_with("2000a"));
+        assert_eq!(text(2), "A. Jones 2001");
+        assert_eq!(text(3), "Brown 2002");
+        assert!(text(4).ends_with("2000b"));
+        assert_eq!(text(5), "B. Jones 2001");
+    }
 }
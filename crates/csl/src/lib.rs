@@ -307,16 +307,20 @@ impl FromNode for Citation {
     const CHILD_DESC: &'static str = "citation";
     fn from_node(node: &Node, info: &ParseInfo) -> FromNodeResult<Self> {
         // TODO: remove collect() using Peekable
-        let layouts: Vec<_> = node
+        let (locale_layouts, layouts): (Vec<_>, Vec<_>) = node
             .children()
             .filter(|n| n.has_tag_name("layout"))
-            .collect();
+            .partition(|n| info.features.locale_layouts && n.has_attribute("locale"));
         if layouts.len() != 1 {
             return Err(
                 InvalidCsl::new(node, "<citation> must contain exactly one <layout>").into(),
             );
         }
         let layout_node = layouts[0];
+        let locale_layouts = locale_layouts
+            .iter()
+            .map(|n| Layout::from_node(n, info))
+            .partition_results()?;
         let sorts: Vec<_> = node.children().filter(|n| n.has_tag_name("sort")).collect();
         if sorts.len() > 1 {
             return Err(InvalidCsl::new(node, "<citation> can only contain one <sort>").into());
@@ -358,6 +362,12 @@ impl FromNode for Citation {
             year_suffix_delimiter: attribute_option(node, "year-suffix-delimiter", info)?,
             after_collapse_delimiter: attribute_option(node, "after-collapse-delimiter", info)?,
             collapse: attribute_option(node, "collapse", info)?,
+            locator_delimiter: if info.features.multiple_locators {
+                attribute_option(node, "locator-delimiter", info)?
+            } else {
+                None
+            },
+            locale_layouts,
             sort,
         })
     }
@@ -452,9 +462,9 @@ impl FromNode for Bibliography {
             );
         }
         let layout_node = layouts[0];
-        let line_spaces = attribute_int(node, "line-spaces", 1)?;
+        let line_spaces = attribute_int(node, "line-spacing", 1)?;
         if line_spaces < 1 {
-            return Err(InvalidCsl::new(node, "line-spaces must be >= 1").into());
+            return Err(InvalidCsl::new(node, "line-spacing must be >= 1").into());
         }
         let entry_spacing = attribute_int(node, "entry-spacing", 1)?;
         let sorts: Vec<_> = node.children().filter(|n| n.has_tag_name("sort")).collect();
@@ -647,6 +657,17 @@ impl FromNode for TextElement {
         let strip_periods = bool::attribute_default_val(node, "strip-periods", info, false)?;
         let text_case = TextCase::from_node(node, info)?;
         let display = attribute_option(node, "display", info)?;
+        let max_length = if info.features.text_max_length {
+            attribute_option_int(node, "text-max-length")?
+        } else {
+            None
+        };
+        let page_range_format = element_page_range_format(node, info)?;
+        let language = if info.features.multilingual_variants {
+            attribute_option(node, "language", info)?
+        } else {
+            None
+        };
 
         Ok(TextElement {
             source,
@@ -656,10 +677,24 @@ impl FromNode for TextElement {
             strip_periods,
             text_case,
             display,
+            max_length,
+            page_range_format,
+            language,
         })
     }
 }
 
+fn element_page_range_format(
+    node: &Node,
+    info: &ParseInfo,
+) -> FromNodeResult<Option<PageRangeFormat>> {
+    if info.features.element_page_range_format {
+        Ok(attribute_option(node, "page-range-format", info)?)
+    } else {
+        Ok(None)
+    }
+}
+
 impl FromNode for NumberElement {
     fn from_node(node: &Node, info: &ParseInfo) -> FromNodeResult<Self> {
         Ok(NumberElement {
@@ -669,6 +704,7 @@ impl FromNode for NumberElement {
             affixes: Option::from_node(node, info)?,
             text_case: attribute_optional(node, "text-case", info)?,
             display: attribute_option(node, "display", info)?,
+            page_range_format: element_page_range_format(node, info)?,
         })
     }
 }
@@ -686,6 +722,11 @@ impl FromNode for Group {
             delimiter: attribute_option(node, "delimiter", info)?,
             affixes: Option::from_node(node, info)?,
             display: attribute_option(node, "display", info)?,
+            text_case: if info.features.group_text_case {
+                attribute_optional(node, "text-case", info)?
+            } else {
+                Default::default()
+            },
             // TODO: CSL-M only
             is_parallel: bool::attribute_default_val(node, "is-parallel", info, false)?,
         })
@@ -753,6 +794,11 @@ impl ConditionParser {
         } else {
             Default::default()
         };
+        let flag = if info.features.reference_flags {
+            attribute_array(node, "flag", info)?
+        } else {
+            Vec::new()
+        };
         let cond = ConditionParser {
             match_type: Match::from_node(node, info)?,
             jurisdiction: attribute_option(node, "jurisdiction", info)?,
@@ -774,6 +820,7 @@ impl ConditionParser {
             has_year_only,
             has_month_or_season,
             has_day,
+            flag,
         };
         // technically, only a match="..." on an <if> is ignored when a <conditions> block is
         // present, but that's ok
@@ -1302,6 +1349,11 @@ impl FromNode for NameEtAl {
         Ok(NameEtAl {
             term: attribute_string(node, "term"),
             formatting: Option::from_node(node, info)?,
+            delimiter: if info.features.et_al_delimiter {
+                attribute_option(node, "delimiter", info)?
+            } else {
+                None
+            },
         })
     }
 }
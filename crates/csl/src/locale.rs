@@ -410,21 +410,28 @@ impl Locale {
     }
 
     pub fn merge(&mut self, with: &Self) {
-        fn extend<K: Clone + Eq + std::hash::Hash, V: Clone>(
-            map: &mut FnvHashMap<K, V>,
-            other: &FnvHashMap<K, V>,
-        ) {
-            map.extend(other.iter().map(|(k, v)| (k.clone(), v.clone())));
-        }
         self.lang = with.lang.clone();
+        self.merge_terms(with);
+        extend(&mut self.dates, &with.dates);
+        self.options_node.merge(&with.options_node);
+    }
+
+    /// Like `merge`, but only takes the other locale's terms, keeping this locale's language,
+    /// date formats and options.
+    pub fn merge_terms(&mut self, with: &Self) {
         extend(&mut self.simple_terms, &with.simple_terms);
         extend(&mut self.gendered_terms, &with.gendered_terms);
         extend(&mut self.role_terms, &with.role_terms);
-        extend(&mut self.dates, &with.dates);
         // replace the whole ordinals configuration if any of them are specified
         if !with.ordinal_terms.is_empty() {
             self.ordinal_terms = with.ordinal_terms.clone();
         }
-        self.options_node.merge(&with.options_node);
     }
 }
+
+fn extend<K: Clone + Eq + std::hash::Hash, V: Clone>(
+    map: &mut FnvHashMap<K, V>,
+    other: &FnvHashMap<K, V>,
+) {
+    map.extend(other.iter().map(|(k, v)| (k.clone(), v.clone())));
+}
@@ -50,6 +50,15 @@ pub struct TextElement {
     pub strip_periods: StripPeriods,
     pub text_case: TextCase,
     pub display: Option<DisplayMode>,
+    /// Behind the `text_max_length` feature. Truncates the rendered variable to this many
+    /// characters, with an ellipsis.
+    pub max_length: Option<u32>,
+    /// Behind the `element_page_range_format` feature. Overrides the style's
+    /// `page-range-format` for this element.
+    pub page_range_format: Option<PageRangeFormat>,
+    /// Behind the `multilingual_variants` feature. Renders the reference's variant of the
+    /// variable in this language, if it has one.
+    pub language: Option<Lang>,
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
@@ -71,6 +80,9 @@ pub struct NumberElement {
     pub affixes: Option<Affixes>,
     pub text_case: TextCase,
     pub display: Option<DisplayMode>,
+    /// Behind the `element_page_range_format` feature. Overrides the style's
+    /// `page-range-format` for this element.
+    pub page_range_format: Option<PageRangeFormat>,
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
@@ -100,6 +112,8 @@ pub struct Group {
     pub affixes: Option<Affixes>,
     pub elements: Vec<Element>,
     pub display: Option<DisplayMode>,
+    /// Applied to the whole rendered group, including any names inside it.
+    pub text_case: TextCase,
     /// CSL-M only
     pub is_parallel: bool,
 }
@@ -398,6 +412,8 @@ pub enum Cond {
     IsPlural(NameVariable),
     Jurisdiction(SmartString),
     SubJurisdiction(u32),
+    /// A host-provided flag on the reference, see the `reference_flags` feature
+    Flag(SmartString),
 }
 
 #[derive(Debug, Eq, Clone, PartialEq)]
@@ -429,6 +445,9 @@ impl From<ConditionParser> for CondSet {
         for x in cp.jurisdiction { conds.insert(Cond::Jurisdiction(x)); }
         for x in cp.subjurisdictions { conds.insert(Cond::SubJurisdiction(x)); }
 
+        // citeproc-rs
+        for x in cp.flag { conds.insert(Cond::Flag(x)); }
+
         CondSet {
             match_type: cp.match_type,
             conds
@@ -475,6 +494,9 @@ pub(crate) struct ConditionParser {
 
     // https://github.com/Juris-M/citeproc-js/blob/30ceaf50a0ef86517a9a8cd46362e450133c7f91/src/attributes.js#L599-L627
     pub is_plural: Vec<NameVariable>,
+
+    /// `flag="..."`, matching flags the host has set on a reference
+    pub flag: Vec<SmartString>,
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -501,6 +523,7 @@ impl ConditionParser {
             && self.subjurisdictions.is_none()
             && self.is_plural.is_empty()
             && self.context.is_none()
+            && self.flag.is_empty()
     }
 }
 
@@ -853,6 +876,9 @@ pub struct NameEtAl {
     // TODO: only accept "et-al" or "and others"
     pub term: String,
     pub formatting: Option<Formatting>,
+    /// Replaces the `<name>` delimiter between the last name and the term, wherever
+    /// `delimiter-precedes-et-al` calls for a delimiter there.
+    pub delimiter: Option<SmartString>,
 }
 
 #[derive(AsRefStr, EnumProperty, EnumString, Debug, Copy, Clone, PartialEq, Eq, Hash)]
@@ -965,6 +991,9 @@ pub struct Citation {
     pub year_suffix_delimiter: Option<SmartString>,
     pub after_collapse_delimiter: Option<SmartString>,
     pub collapse: Option<Collapse>,
+    pub locator_delimiter: Option<SmartString>,
+    /// Layouts with a `locale` attribute, with the `locale-layouts` feature.
+    pub locale_layouts: Vec<Layout>,
 }
 
 impl Default for Citation {
@@ -983,11 +1012,35 @@ impl Default for Citation {
             year_suffix_delimiter: None,
             after_collapse_delimiter: None,
             collapse: None,
+            locator_delimiter: None,
+            locale_layouts: Vec::new(),
         }
     }
 }
 
 impl Citation {
+    /// The layout for a cite of a reference in `lang`: a locale layout listing that language,
+    /// then one listing the same language in another region, otherwise the default layout.
+    pub fn layout_for(&self, lang: Option<&Lang>) -> &Layout {
+        let lang = match lang {
+            Some(lang) if !self.locale_layouts.is_empty() => lang,
+            _ => return &self.layout,
+        };
+        let same_language = |other: &Lang| match (lang, other) {
+            (Lang::Iso(a, _), Lang::Iso(b, _)) => a == b,
+            _ => lang == other,
+        };
+        self.locale_layouts
+            .iter()
+            .find(|l| l.locale.contains(lang))
+            .or_else(|| {
+                self.locale_layouts
+                    .iter()
+                    .find(|l| l.locale.iter().any(same_language))
+            })
+            .unwrap_or(&self.layout)
+    }
+
     /// Implements fallback to Year when disambiguate-add-year-suffix is false.
     pub fn collapse_fallback(&self) -> Option<Collapse> {
         let addyearsuf = self.disambiguate_add_year_suffix;
@@ -221,6 +221,18 @@ impl GenderedTermSelector {
 
     pub fn fallback(self) -> Box<dyn Iterator<Item = Self>> {
         match self {
+            // Older locales have no number-of-* terms, so fall back to the page and volume terms
+            // once every form of the quantity term has been tried.
+            GenderedTermSelector::Number(t @ NumberVariable::NumberOfPages, form) => Box::new(
+                form.fallback()
+                    .map(move |x| GenderedTermSelector::Number(t, x))
+                    .chain(GenderedTermSelector::Locator(LocatorType::Page, form).fallback()),
+            ),
+            GenderedTermSelector::Number(t @ NumberVariable::NumberOfVolumes, form) => Box::new(
+                form.fallback()
+                    .map(move |x| GenderedTermSelector::Number(t, x))
+                    .chain(GenderedTermSelector::Locator(LocatorType::Volume, form).fallback()),
+            ),
             GenderedTermSelector::Number(t, form) => Box::new(
                 form.fallback()
                     .map(move |x| GenderedTermSelector::Number(t, x)),
@@ -684,6 +696,9 @@ pub enum MiscTerm {
     // https://github.com/Juris-M/citeproc-js/blob/30ceaf50a0ef86517a9a8cd46362e450133c7f91/src/node_datepart.js#L164-L176
     PageRangeDelimiter,
     YearRangeDelimiter,
+
+    // CSL 1.0.2
+    CitationRangeDelimiter,
 }
 
 /// [Spec](https://docs.citationstyles.org/en/stable/specification.html#months)
@@ -266,6 +266,38 @@ declare_features!(
     (active, legal_locators, "1.0.1", None, None),
     /// `<text term="unpublished">`
     (active, term_unpublished, "1.0.1", None, None),
+    /// `<text variable="abstract" text-max-length="100">`, truncating a variable's output to a
+    /// number of characters with an ellipsis
+    (active, text_max_length, "1.0.1", None, None),
+    /// `<text variable="page" page-range-format="expanded">` (and on `<number>`), overriding the
+    /// style's page-range-format for one element
+    (active, element_page_range_format, "1.0.1", None, None),
+    /// `subsequent-author-substitute` also replaces the second and later `<names>` blocks of a
+    /// bibliography entry (e.g. author, then editor) while they match the previous entry's blocks
+    (active, subsequent_author_substitute_all_names, "1.0.1", None, None),
+    /// `<if flag="peer-reviewed">`, testing flags the host program has set on a reference with
+    /// `Processor::set_reference_flags`
+    (active, reference_flags, "1.0.1", None, None),
+    /// `<text variable="title" language="en">`, rendering a reference's translated variant of a
+    /// variable from the CSL-M `multi` field, falling back to the untranslated value
+    (active, multilingual_variants, "1.0.1", None, None),
+    /// `<number form="numeric">` renders only the first number in a value with other text
+    /// around it, e.g. `3` for `Volume 3`, instead of the whole value
+    (active, number_extract_numeric, "1.0.1", None, None),
+    /// Renders every locator of a cite with more than one, each after the first with its own
+    /// label, joined by `<citation locator-delimiter="...">` (default `, `)
+    (active, multiple_locators, "1.0.1", None, None),
+    /// Extra `<citation><layout locale="zh">` elements, used for cites of references in those
+    /// languages, with the cluster's delimiter and affixes taken from its first cite's layout
+    (active, locale_layouts, "1.0.1", None, None),
+    /// Renders each reference with the locale for its `language` field, so terms, dates and
+    /// ordinals follow the reference's language instead of the style's default locale
+    (active, reference_locale, "1.0.1", None, None),
+    /// `<group text-case="uppercase">`, applying a text-case to everything the group renders,
+    /// including names and terms
+    (active, group_text_case, "1.0.1", None, None),
+    /// `<et-al delimiter=" ">`, replacing the name delimiter written before the et-al term
+    (active, et_al_delimiter, "1.0.1", None, None),
 );
 
 // status, name, first added version, tracking issue, edition, None
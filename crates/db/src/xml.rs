@@ -23,6 +23,12 @@ pub trait HasFetcher {
 pub trait StyleDatabase {
     #[salsa::input]
     fn style(&self) -> Arc<Style>;
+    /// Replaces the style's `near-note-distance`, set at runtime instead of in the style.
+    #[salsa::input]
+    fn near_note_distance_override(&self) -> Option<u32>;
+
+    /// How many notes back a cite can be and still be `near-note`
+    fn near_note_distance(&self) -> u32;
 
     /// Grabs the Name options from `<style>` + `<citation>` elements
     /// First one is the inherited names-delimiter
@@ -35,6 +41,11 @@ pub trait StyleDatabase {
     fn name_configurations(&self) -> Arc<Vec<(NameVariable, Name)>>;
 }
 
+fn near_note_distance(db: &dyn StyleDatabase) -> u32 {
+    db.near_note_distance_override()
+        .unwrap_or_else(|| db.style().citation.near_note_distance)
+}
+
 fn name_info_citation(db: &dyn StyleDatabase) -> (Option<SmartString>, Arc<Name>) {
     let style = db.style();
     style.name_info_citation()
@@ -112,7 +123,8 @@ fn name_configurations_inner(
 fn name_configurations_middle(style: &Style) -> Vec<(NameVariable, Name)> {
     let base = style.name_citation();
     let mut vec = Vec::new();
-    for el in &style.citation.layout.elements {
+    let layouts = std::iter::once(&style.citation.layout).chain(&style.citation.locale_layouts);
+    for el in layouts.flat_map(|l| &l.elements) {
         name_configurations_inner(style, &base, el, &mut vec);
     }
     vec
@@ -170,6 +182,12 @@ pub trait LocaleDatabase: StyleDatabase + HasFetcher {
     fn locale_input_langs(&self) -> Arc<FnvHashSet<Lang>>;
     #[salsa::input]
     fn default_lang_override(&self) -> Option<Lang>;
+    /// Renders the bibliography in a different language to the citations.
+    #[salsa::input]
+    fn bibliography_lang_override(&self) -> Option<Lang>;
+    /// Terms that replace those of every merged locale, set at runtime instead of in the style.
+    #[salsa::input]
+    fn term_overrides(&self) -> Arc<Locale>;
 
     /// Backed by the LocaleFetcher implementation
     #[salsa::transparent]
@@ -188,8 +206,17 @@ pub trait LocaleDatabase: StyleDatabase + HasFetcher {
 
     fn default_locale(&self) -> Arc<Locale>;
 
+    /// A memoized `merged_locale`, for rendering references in their own languages.
+    fn lang_locale(&self, key: Lang) -> Arc<Locale>;
+
     #[salsa::transparent]
     fn default_lang(&self) -> Lang;
+
+    /// The locale used for the bibliography, which is the default locale unless overridden.
+    fn bibliography_locale(&self) -> Arc<Locale>;
+
+    #[salsa::transparent]
+    fn bibliography_lang(&self) -> Lang;
 }
 
 fn default_lang(db: &dyn LocaleDatabase) -> Lang {
@@ -205,6 +232,19 @@ fn default_locale(db: &dyn LocaleDatabase) -> Arc<Locale> {
     db.merged_locale(db.default_lang())
 }
 
+fn lang_locale(db: &dyn LocaleDatabase, key: Lang) -> Arc<Locale> {
+    db.merged_locale(key)
+}
+
+fn bibliography_lang(db: &dyn LocaleDatabase) -> Lang {
+    db.bibliography_lang_override()
+        .unwrap_or_else(|| db.default_lang())
+}
+
+fn bibliography_locale(db: &dyn LocaleDatabase) -> Arc<Locale> {
+    db.merged_locale(db.bibliography_lang())
+}
+
 fn locale_xml(db: &dyn LocaleDatabase, key: Lang) -> Option<Arc<String>> {
     let stored = db.locale_input_langs();
     if stored.contains(&key) {
@@ -249,23 +289,23 @@ fn merged_locale(db: &dyn LocaleDatabase, key: Lang) -> Arc<Locale> {
         .iter()
         .filter_map(|src| db.parsed_locale(src))
         .collect::<Vec<_>>();
-    Arc::new(
-        locales
-            .into_iter()
-            .rev()
-            .fold(None, |mut acc, l| match acc {
-                None => Some((*l).clone()),
-                Some(ref mut base) => {
-                    debug!("merging locales: {:?} <- {:?}", base.lang, l.lang);
-                    base.merge(&l);
-                    acc
-                }
-            })
-            .unwrap_or_else(|| {
-                warn!("Using default, empty locale");
-                Locale::default()
-            }),
-    )
+    let mut merged = locales
+        .into_iter()
+        .rev()
+        .fold(None, |mut acc, l| match acc {
+            None => Some((*l).clone()),
+            Some(ref mut base) => {
+                debug!("merging locales: {:?} <- {:?}", base.lang, l.lang);
+                base.merge(&l);
+                acc
+            }
+        })
+        .unwrap_or_else(|| {
+            warn!("Using default, empty locale");
+            Locale::default()
+        });
+    merged.merge_terms(&db.term_overrides());
+    Arc::new(merged)
 }
 
 use std::panic::RefUnwindSafe;
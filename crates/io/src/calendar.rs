@@ -0,0 +1,178 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2020 Corporation for Digital Scholarship
+
+//! Dates given in calendars other than the Gregorian one.
+//!
+//! These are converted to Gregorian as they are read, so they sort and compare with every other
+//! date, but remember their calendar so their year can be rendered the way it was given.
+
+use crate::date::Date;
+
+/// The calendar a date was given in. A [Date](struct.Date.html)'s fields are always Gregorian.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Calendar {
+    Gregorian,
+    /// A year of a Japanese era, e.g. 令和2年. Japan's months and days are Gregorian ones.
+    Japanese(JapaneseEra),
+    /// A year of the Hebrew calendar, e.g. 5780. Only the year is converted, to the Gregorian
+    /// year most of it falls in; Hebrew months and days are not kept.
+    Hebrew,
+}
+
+impl Default for Calendar {
+    fn default() -> Self {
+        Calendar::Gregorian
+    }
+}
+
+/// Hebrew years start in the autumn, so most of 5780 falls in 2020.
+const HEBREW_OFFSET: i32 = 3760;
+
+impl Calendar {
+    /// The calendars that can be named in CSL-JSON, as `{ "calendar": "hebrew" }`. Japanese eras
+    /// are recognised from dates like 令和2年 instead.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "gregorian" => Some(Calendar::Gregorian),
+            "hebrew" => Some(Calendar::Hebrew),
+            _ => None,
+        }
+    }
+
+    /// Converts a year in this calendar to a Gregorian one.
+    pub fn gregorian_year(self, year: i32) -> i32 {
+        match self {
+            Calendar::Gregorian => year,
+            Calendar::Japanese(era) => era.first_year() + year - 1,
+            Calendar::Hebrew => year - HEBREW_OFFSET,
+        }
+    }
+
+    /// Converts a Gregorian year back to this calendar.
+    pub fn calendar_year(self, year: i32) -> i32 {
+        match self {
+            Calendar::Gregorian => year,
+            Calendar::Japanese(era) => year - era.first_year() + 1,
+            Calendar::Hebrew => year + HEBREW_OFFSET,
+        }
+    }
+}
+
+/// The modern Japanese eras (gengō).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum JapaneseEra {
+    Meiji,
+    Taisho,
+    Showa,
+    Heisei,
+    Reiwa,
+}
+
+impl JapaneseEra {
+    const ALL: [JapaneseEra; 5] = [
+        JapaneseEra::Meiji,
+        JapaneseEra::Taisho,
+        JapaneseEra::Showa,
+        JapaneseEra::Heisei,
+        JapaneseEra::Reiwa,
+    ];
+
+    /// The Gregorian year of the era's first year (元年).
+    pub fn first_year(self) -> i32 {
+        match self {
+            JapaneseEra::Meiji => 1868,
+            JapaneseEra::Taisho => 1912,
+            JapaneseEra::Showa => 1926,
+            JapaneseEra::Heisei => 1989,
+            JapaneseEra::Reiwa => 2019,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            JapaneseEra::Meiji => "明治",
+            JapaneseEra::Taisho => "大正",
+            JapaneseEra::Showa => "昭和",
+            JapaneseEra::Heisei => "平成",
+            JapaneseEra::Reiwa => "令和",
+        }
+    }
+}
+
+fn take_number(s: &str) -> Option<(u32, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    if end == 0 {
+        return None;
+    }
+    Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+/// Parses a Japanese era date like 令和2年, 令和元年5月 or 平成31年4月30日.
+pub(crate) fn parse_japanese(s: &str) -> Option<Date> {
+    let s = s.trim();
+    let era = JapaneseEra::ALL
+        .iter()
+        .copied()
+        .find(|era| s.starts_with(era.name()))?;
+    let rest = &s[era.name().len()..];
+    let (year, rest) = match rest.strip_prefix('元') {
+        Some(rest) => (1, rest),
+        None => take_number(rest)?,
+    };
+    let rest = rest.strip_prefix('年')?;
+    let (month, rest) = match take_number(rest) {
+        Some((month, rest)) => (month, rest.strip_prefix('月')?),
+        None => (0, rest),
+    };
+    let (day, rest) = match take_number(rest) {
+        Some((day, rest)) if month != 0 => (day, rest.strip_prefix('日')?),
+        _ => (0, rest),
+    };
+    if !rest.is_empty() || year == 0 || month > 12 || day > 31 {
+        return None;
+    }
+    let calendar = Calendar::Japanese(era);
+    Some(Date {
+        year: calendar.gregorian_year(year as i32),
+        month,
+        day,
+        circa: false,
+        calendar,
+    })
+}
+
+#[test]
+fn test_parse_japanese() {
+    let reiwa = Calendar::Japanese(JapaneseEra::Reiwa);
+    let heisei = Calendar::Japanese(JapaneseEra::Heisei);
+    let date = |year, month, day, calendar| Date {
+        year,
+        month,
+        day,
+        circa: false,
+        calendar,
+    };
+    assert_eq!(parse_japanese("令和2年"), Some(date(2020, 0, 0, reiwa)));
+    assert_eq!(parse_japanese("令和元年5月"), Some(date(2019, 5, 0, reiwa)));
+    assert_eq!(
+        parse_japanese("平成31年4月30日"),
+        Some(date(2019, 4, 30, heisei))
+    );
+    assert_eq!(
+        parse_japanese("昭和64年"),
+        Some(date(1989, 0, 0, Calendar::Japanese(JapaneseEra::Showa)))
+    );
+    assert_eq!(parse_japanese("令和2"), None);
+    assert_eq!(parse_japanese("令和0年"), None);
+    assert_eq!(parse_japanese("令和2年13月"), None);
+    assert_eq!(parse_japanese("2020年"), None);
+}
+
+#[test]
+fn test_hebrew_years() {
+    assert_eq!(Calendar::Hebrew.gregorian_year(5780), 2020);
+    assert_eq!(Calendar::Hebrew.calendar_year(2020), 5780);
+}
@@ -53,9 +53,9 @@ pub struct Cite<O: OutputFormat> {
     #[serde(default)]
     pub suffix: Option<O::Input>,
 
-    /// Multiple locator functionality needs CSL support, so it is disabled via using
-    /// `Locators::single_locator` for now.
-    #[serde(default, flatten, deserialize_with = "Locators::single_locator")]
+    /// Either a single `locator` and `label`, or a `locators` array of them. Locators after the
+    /// first are only rendered by styles with the `multiple-locators` feature.
+    #[serde(default, flatten, deserialize_with = "Locators::get_locators")]
     pub locators: Option<Locators>,
 
     #[serde(default, flatten)]
@@ -123,7 +123,7 @@ pub struct CiteCompat {
     #[serde(default)]
     pub suffix: Option<String>,
 
-    #[serde(default, flatten, deserialize_with = "Locators::single_locator")]
+    #[serde(default, flatten, deserialize_with = "Locators::get_locators")]
     pub locators: Option<Locators>,
 
     #[serde(default, flatten, deserialize_with = "CiteMode::compat")]
@@ -171,7 +171,7 @@ where
 
 /// Accepts either
 /// `{ "locator": "54", "label": "page" }` or
-/// `{ "locators": [["chapter", "19"], ["page", "581"]] }`.
+/// `{ "locators": [{ "label": "chapter", "locator": "19" }, { "label": "page", "locator": "581" }] }`.
 #[derive(Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[serde(untagged)]
 pub enum Locators {
@@ -186,8 +186,13 @@ impl Locators {
             Locators::Multiple { locators } => locators.get(0),
         }
     }
-    // Used by get_locators
-    #[allow(dead_code)]
+    /// Any locators after the first.
+    pub fn rest(&self) -> &[Locator] {
+        match self {
+            Locators::Single(_) => &[],
+            Locators::Multiple { locators } => locators.get(1..).unwrap_or(&[]),
+        }
+    }
     fn into_option(self) -> Option<Self> {
         match self {
             Locators::Multiple { locators } => {
@@ -204,21 +209,8 @@ impl Locators {
         }
     }
 
-    /// Only accepts `"locator": "abc", "label": "page|etc"`, no arrays.
-    ///
-    /// Locates singles in your area
-    fn single_locator<'de, D>(d: D) -> Result<Option<Locators>, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        Ok(Option::<Locator>::deserialize(d)?.map(Locators::Single))
-    }
-
     /// Single length locators arrays => Some(Locators::Single)
     /// Zero length => None
-    ///
-    /// Not yet used, pending CSL 1.1 locator array
-    #[allow(dead_code)]
     fn get_locators<'de, D>(d: D) -> Result<Option<Locators>, D::Error>
     where
         D: Deserializer<'de>,
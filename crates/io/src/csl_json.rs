@@ -28,6 +28,7 @@ use csl::Features;
 use csl::GetAttribute;
 use csl::Lang;
 
+use super::calendar::{self, Calendar};
 use super::date::{Date, DateOrRange};
 use super::reference::Reference;
 use fnv::FnvHashMap;
@@ -56,6 +57,17 @@ impl<'de> Visitor<'de> for LanguageVisitor {
 
 pub struct MaybeDate(Option<DateOrRange>);
 
+/// Reads a date string as EDTF, a Japanese era date or ISO 8601, or else keeps it as a literal.
+fn date_from_str(s: &str) -> DateOrRange {
+    DateOrRange::from_edtf(s)
+        .or_else(|| calendar::parse_japanese(s).map(DateOrRange::Single))
+        .or_else(|| DateOrRange::from_str(s).ok())
+        .unwrap_or_else(|| DateOrRange::Literal {
+            literal: s.into(),
+            circa: false,
+        })
+}
+
 pub struct WrapLang(Option<Lang>);
 
 impl<'de> Deserialize<'de> for WrapLang {
@@ -95,6 +107,7 @@ enum Field<'a> {
     Id,
     Type,
     Language,
+    Multi,
     // don't use plain `&'a str`, because that would fail when parsing from a serde::Value.
     #[serde(borrow, deserialize_with = "cow_str::deserialize_cow_str")]
     Any(Cow<'a, str>),
@@ -122,6 +135,34 @@ impl NumberLike {
     }
 }
 
+/// Seasons are 1-4 (or 21-24, as in EDTF), or the English season names, which are then rendered
+/// using the locale's season-01..04 terms.
+fn parse_season(season: &NumberLike) -> Option<u32> {
+    let num = match season {
+        NumberLike::Num(n) => *n,
+        NumberLike::Str(s) => match s.trim().to_lowercase().as_str() {
+            "spring" => 1,
+            "summer" => 2,
+            "autumn" | "fall" => 3,
+            "winter" => 4,
+            other => other.parse().ok()?,
+        },
+    };
+    match num {
+        1..=4 => Some(num),
+        21..=24 => Some(num - 20),
+        _ => None,
+    }
+}
+
+/// Ordinary variables are usually strings, but repeatable fields may be given as an array.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OrdinaryValue {
+    One(String),
+    Many(Vec<String>),
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(untagged)]
 enum CircaValue {
@@ -177,6 +218,9 @@ impl<'de> Deserialize<'de> for WrapVar {
     }
 }
 
+/// CSL-JSON fields that are valid, but are not variables a style could ask for.
+const NON_VARIABLE_FIELDS: &[&str] = &["categories", "custom"];
+
 impl<'de> Deserialize<'de> for Reference {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
@@ -199,9 +243,12 @@ impl<'de> Deserialize<'de> for Reference {
                 let mut csl_type: Option<WrapType> = None;
                 let mut language = None;
                 let mut ordinary = FnvHashMap::default();
+                let mut multi = FnvHashMap::default();
                 let mut number = FnvHashMap::default();
                 let mut name = FnvHashMap::default();
                 let mut date = FnvHashMap::default();
+                let mut variants = FnvHashMap::default();
+                let mut unknown_variables = Vec::new();
                 while let Some(key) = map.next_key()? {
                     match key {
                         Field::Id => {
@@ -214,16 +261,29 @@ impl<'de> Deserialize<'de> for Reference {
                             let wrap: WrapLang = map.next_value()?;
                             language = wrap.0;
                         }
+                        Field::Multi => {
+                            let multi: MultiFields = map.next_value()?;
+                            variants = multi.into_variants();
+                        }
                         Field::Any(var_name) => {
                             match AnyVariable::get_attr(&var_name, &Features::default()) {
                                 Err(_unknown) => {
                                     // Unknown variable. Let it slide.
                                     log::warn!("reference had unknown variable `{}`", var_name);
                                     let _: IgnoredAny = map.next_value()?;
+                                    if !NON_VARIABLE_FIELDS.contains(&var_name.as_ref()) {
+                                        unknown_variables.push(var_name.into_owned());
+                                    }
                                 }
-                                Ok(AnyVariable::Ordinary(v)) => {
-                                    ordinary.insert(v, map.next_value()?);
-                                }
+                                Ok(AnyVariable::Ordinary(v)) => match map.next_value()? {
+                                    OrdinaryValue::One(s) => {
+                                        ordinary.insert(v, s);
+                                    }
+                                    OrdinaryValue::Many(values) => {
+                                        ordinary.insert(v, values.join(", "));
+                                        multi.insert(v, values);
+                                    }
+                                },
                                 Ok(AnyVariable::Number(v)) => {
                                     number.insert(v, map.next_value()?);
                                 }
@@ -247,9 +307,13 @@ impl<'de> Deserialize<'de> for Reference {
                     csl_type: csl_type.unwrap_or(WrapType(CslType::Article)).0,
                     language,
                     ordinary,
+                    multi,
                     number,
                     name,
                     date,
+                    variants,
+                    flags: Default::default(),
+                    unknown_variables,
                 })
             }
         }
@@ -259,6 +323,39 @@ impl<'de> Deserialize<'de> for Reference {
     }
 }
 
+/// CSL-M's `multi` field. Only `_keys` (translations of whole variables) is used.
+#[derive(Deserialize)]
+struct MultiFields {
+    #[serde(rename = "_keys", default)]
+    keys: FnvHashMap<String, FnvHashMap<String, String>>,
+}
+
+impl MultiFields {
+    fn into_variants(self) -> FnvHashMap<csl::Variable, Vec<(Lang, String)>> {
+        let mut variants = FnvHashMap::default();
+        for (var_name, translations) in self.keys {
+            let var = match AnyVariable::get_attr(&var_name, &Features::default()) {
+                Ok(AnyVariable::Ordinary(v)) => v,
+                _ => {
+                    log::warn!(
+                        "reference had multi variants for unsupported variable `{}`",
+                        var_name
+                    );
+                    continue;
+                }
+            };
+            let mut list: Vec<(Lang, String)> = translations
+                .into_iter()
+                .filter_map(|(lang, value)| Some((lang.parse().ok()?, value)))
+                .collect();
+            // HashMap order is arbitrary; keep lookups deterministic
+            list.sort();
+            variants.insert(var, list);
+        }
+        variants
+    }
+}
+
 // newtype these so we can have a different implementation
 struct DateParts(Option<DateOrRange>);
 
@@ -452,6 +549,7 @@ impl<'de> Deserialize<'de> for MaybeDate {
             Raw,
             Year,
             Edtf,
+            Calendar,
             #[serde(borrow, deserialize_with = "cow_str::deserialize_cow_str")]
             Unknown(Cow<'a, str>),
         }
@@ -469,28 +567,14 @@ impl<'de> Deserialize<'de> for MaybeDate {
             where
                 E: de::Error,
             {
-                FromStr::from_str(value)
-                    .or_else(|_| {
-                        Ok(DateOrRange::Literal {
-                            literal: value.into(),
-                            circa: false,
-                        })
-                    })
-                    .map(|x| MaybeDate(Some(x)))
+                Ok(MaybeDate(Some(date_from_str(value))))
             }
 
             fn visit_string<E>(self, value: String) -> Result<Self::Value, E>
             where
                 E: de::Error,
             {
-                FromStr::from_str(&value)
-                    .or_else(|_| {
-                        Ok(DateOrRange::Literal {
-                            literal: value.into(),
-                            circa: false,
-                        })
-                    })
-                    .map(|x| MaybeDate(Some(x)))
+                Ok(MaybeDate(Some(date_from_str(&value))))
             }
 
             fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
@@ -500,17 +584,13 @@ impl<'de> Deserialize<'de> for MaybeDate {
                 let mut found = None;
                 let mut found_season: Option<NumberLike> = None;
                 let mut found_circa: Option<bool> = None;
+                let mut found_calendar: Option<Calendar> = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         DateType::Raw => {
                             let v: Cow<'de, str> = map.next_value()?;
                             if found.is_none() {
-                                found = Some(DateOrRange::from_str(&v).unwrap_or_else(|_| {
-                                    DateOrRange::Literal {
-                                        literal: v.as_ref().into(),
-                                        circa: false,
-                                    }
-                                }))
+                                found = Some(date_from_str(&v))
                             }
                         }
                         DateType::Literal => {
@@ -532,9 +612,15 @@ impl<'de> Deserialize<'de> for MaybeDate {
                             }
                         }
                         DateType::Edtf => {
-                            log::warn!("unimplemented: edtf date support");
-                            let _: IgnoredAny = map.next_value()?;
-                            continue;
+                            let v: Cow<'de, str> = map.next_value()?;
+                            match DateOrRange::from_edtf(&v) {
+                                Some(edtf) => {
+                                    if found.is_none() {
+                                        found = Some(edtf)
+                                    }
+                                }
+                                None => log::warn!("invalid edtf date: {}", v),
+                            }
                         }
                         DateType::Season => found_season = Some(map.next_value()?),
                         DateType::Circa => {
@@ -544,6 +630,13 @@ impl<'de> Deserialize<'de> for MaybeDate {
                                 log::warn!("invalid value for circa");
                             }
                         }
+                        DateType::Calendar => {
+                            let v: Cow<'de, str> = map.next_value()?;
+                            match Calendar::from_name(&v) {
+                                Some(calendar) => found_calendar = Some(calendar),
+                                None => log::warn!("unknown calendar: {}", v),
+                            }
+                        }
                         DateType::Year => {
                             if let Ok(year) = map.next_value() {
                                 let date = Date {
@@ -551,6 +644,7 @@ impl<'de> Deserialize<'de> for MaybeDate {
                                     month: 0,
                                     day: 0,
                                     circa: false,
+                                    calendar: Calendar::Gregorian,
                                 };
                                 found = Some(DateOrRange::Single(date));
                             }
@@ -566,39 +660,39 @@ impl<'de> Deserialize<'de> for MaybeDate {
                     // .ok_or_else(|| de::Error::missing_field("raw|literal|etc"))
                     .and_then(|mut found| {
                         if let Some(season) = found_season {
+                            let mut literal = None;
                             if let DateOrRange::Single(ref mut date) = found {
-                                if !date.has_day() && !date.has_month() {
-                                    let season = season
-                                        .to_number()
-                                        .map_err(|e| {
-                                            V::Error::custom(format!(
-                                                "season {:?} was not an integer: {}",
-                                                season, e
-                                            ))
-                                        })
-                                        .and_then(|unsigned| {
-                                            if unsigned < 1 || unsigned > 4 {
-                                                Err(V::Error::custom(format!(
-                                                    "season {} was not in range [1, 4]",
-                                                    unsigned
-                                                )))
-                                            } else {
-                                                Ok(unsigned as u32)
-                                            }
-                                        });
-                                    if let Ok(mut season) = season {
-                                        if season > 20 {
-                                            // handle 21, 22, 23, 24
-                                            season -= 20;
+                                // A month is more specific, but a season replaces a bare day,
+                                // which means nothing without a month.
+                                if !date.has_month() {
+                                    match (parse_season(&season), season) {
+                                        (Some(season), _) => {
+                                            date.month = season + 12;
+                                            date.day = 0;
+                                        }
+                                        // There is no term for it, so it is rendered as given.
+                                        (None, NumberLike::Str(season)) => {
+                                            literal = Some(DateOrRange::Literal {
+                                                literal: format!("{} {}", season.trim(), date.year),
+                                                circa: date.circa,
+                                            });
+                                        }
+                                        (None, season) => {
+                                            log::warn!("unrecognised season {:?}", season)
                                         }
-                                        date.month = season + 12;
                                     }
                                 }
                             }
+                            if let Some(literal) = literal {
+                                found = literal;
+                            }
                         }
                         if let Some(circa) = found_circa {
                             found.set_circa(circa)
                         }
+                        if let Some(calendar) = found_calendar {
+                            found.set_calendar(calendar)
+                        }
                         Ok(MaybeDate(Some(found)))
                     })
                     .ok()
@@ -606,7 +700,15 @@ impl<'de> Deserialize<'de> for MaybeDate {
             }
         }
 
-        const DATE_TYPES: &[&str] = &["date-parts", "season", "circa", "literal", "raw"];
+        const DATE_TYPES: &[&str] = &[
+            "date-parts",
+            "season",
+            "circa",
+            "literal",
+            "raw",
+            "edtf",
+            "calendar",
+        ];
         deserializer.deserialize_struct("DateOrRange", DATE_TYPES, DateVisitor)
     }
 }
@@ -4,6 +4,7 @@
 //
 // Copyright © 2018 Corporation for Digital Scholarship
 
+use crate::calendar::Calendar;
 use crate::String;
 use std::cmp::Ordering;
 
@@ -25,6 +26,8 @@ pub struct Date {
     pub day: u32,
     /// aka is_uncertain_date
     pub circa: bool,
+    /// The calendar the date was given in, which is only used to render the year the same way.
+    pub calendar: Calendar,
 }
 
 impl PartialOrd for Date {
@@ -88,6 +91,7 @@ impl Date {
             month: m,
             day: d,
             circa: false,
+            calendar: Calendar::Gregorian,
         }
     }
     pub fn from_parts(parts: &[i32]) -> Option<Self> {
@@ -98,6 +102,7 @@ impl Date {
             month: if m >= 1 && m <= 16 { m as u32 } else { 0 },
             day: if d >= 1 && d <= 31 { d as u32 } else { 0 },
             circa: false,
+            calendar: Calendar::Gregorian,
         })
     }
 
@@ -138,6 +143,28 @@ impl DateOrRange {
             DateOrRange::Literal { circa: c, .. } => *c = circa,
         }
     }
+    /// Reads the years of a date that was given in `calendar`, converting them to Gregorian
+    /// ones. Dates already in another calendar are left alone.
+    pub fn set_calendar(&mut self, calendar: Calendar) {
+        let convert = |d: &mut Date| {
+            if d.calendar == Calendar::Gregorian && d.year != 0 {
+                d.year = calendar.gregorian_year(d.year);
+                d.calendar = calendar;
+                if calendar == Calendar::Hebrew {
+                    d.month = 0;
+                    d.day = 0;
+                }
+            }
+        };
+        match self {
+            DateOrRange::Single(d) => convert(d),
+            DateOrRange::Range(d1, d2) => {
+                convert(d1);
+                convert(d2);
+            }
+            DateOrRange::Literal { .. } => {}
+        }
+    }
     pub fn is_uncertain_date(&self) -> bool {
         match *self {
             DateOrRange::Single(d) => d.circa,
@@ -171,6 +198,21 @@ impl DateOrRange {
             ))
         }
     }
+    /// Parses the parts of EDTF (Extended Date/Time Format) level 1 that this date model can
+    /// represent. Uncertain (`?`), approximate (`~`) or both (`%`) dates are marked as circa.
+    /// Unspecified year digits (`19XX`) become a range over every year they could be, unspecified
+    /// months and days (`1984-XX`) are left out, and seasons (`2004-21`) map to CSL seasons. An
+    /// open (`..`) or unknown (empty) end of an interval is an open range.
+    ///
+    /// Returns `None` unless the whole string is EDTF.
+    pub fn from_edtf(s: &str) -> Option<Self> {
+        match edtf_range(s.as_bytes()) {
+            Ok((b"", DateOrRange::Single(d))) if d.year == 0 => None,
+            Ok((b"", DateOrRange::Range(d1, d2))) if d1.year == 0 && d2.year == 0 => None,
+            Ok((b"", parsed)) => Some(parsed),
+            _ => None,
+        }
+    }
 }
 
 impl From<Date> for DateOrRange {
@@ -268,6 +310,104 @@ fn test_range_parsing() {
     );
 }
 
+#[cfg(test)]
+#[test]
+fn test_edtf_parsing() {
+    let edtf = DateOrRange::from_edtf;
+    assert_eq!(
+        edtf("1984?"),
+        Some(DateOrRange::Single(Date::new_circa(1984, 0, 0)))
+    );
+    assert_eq!(
+        edtf("1984~"),
+        Some(DateOrRange::Single(Date::new_circa(1984, 0, 0)))
+    );
+    assert_eq!(
+        edtf("1984%"),
+        Some(DateOrRange::Single(Date::new_circa(1984, 0, 0)))
+    );
+    assert_eq!(
+        edtf("2004-06~"),
+        Some(DateOrRange::Single(Date::new_circa(2004, 6, 0)))
+    );
+    assert_eq!(edtf("2004-06-11"), Some(DateOrRange::new(2004, 6, 11)));
+    // seasons
+    assert_eq!(edtf("2004-21"), Some(DateOrRange::new(2004, 13, 0)));
+    // unspecified digits
+    assert_eq!(
+        edtf("19XX"),
+        Some(DateOrRange::Range(
+            Date::new(1900, 0, 0),
+            Date::new(1999, 0, 0)
+        ))
+    );
+    assert_eq!(
+        edtf("190X"),
+        Some(DateOrRange::Range(
+            Date::new(1900, 0, 0),
+            Date::new(1909, 0, 0)
+        ))
+    );
+    assert_eq!(edtf("1984-XX"), Some(DateOrRange::new(1984, 0, 0)));
+    assert_eq!(edtf("1984-XX-XX"), Some(DateOrRange::new(1984, 0, 0)));
+    assert_eq!(
+        edtf("-19XX"),
+        Some(DateOrRange::Range(
+            Date::new(-1999, 0, 0),
+            Date::new(-1900, 0, 0)
+        ))
+    );
+    // intervals
+    assert_eq!(
+        edtf("1964/2008"),
+        Some(DateOrRange::Range(
+            Date::new(1964, 0, 0),
+            Date::new(2008, 0, 0)
+        ))
+    );
+    assert_eq!(
+        edtf("1964~/2008-06"),
+        Some(DateOrRange::Range(
+            Date::new_circa(1964, 0, 0),
+            Date::new(2008, 6, 0)
+        ))
+    );
+    assert_eq!(
+        edtf("19XX/2008"),
+        Some(DateOrRange::Range(
+            Date::new(1900, 0, 0),
+            Date::new(2008, 0, 0)
+        ))
+    );
+    assert_eq!(
+        edtf("1964/.."),
+        Some(DateOrRange::Range(
+            Date::new(1964, 0, 0),
+            Date::new(0, 0, 0)
+        ))
+    );
+    assert_eq!(
+        edtf("1964/"),
+        Some(DateOrRange::Range(
+            Date::new(1964, 0, 0),
+            Date::new(0, 0, 0)
+        ))
+    );
+    assert_eq!(
+        edtf("../2008"),
+        Some(DateOrRange::Range(
+            Date::new(0, 0, 0),
+            Date::new(2008, 0, 0)
+        ))
+    );
+    // not EDTF
+    assert_eq!(edtf("1998trailing"), None);
+    assert_eq!(edtf("1998-13"), None);
+    assert_eq!(edtf(".."), None);
+    assert_eq!(edtf("../.."), None);
+    assert_eq!(edtf(""), None);
+}
+
 #[cfg(test)]
 #[test]
 fn test_from_parts() {
@@ -485,3 +625,94 @@ fn range(inp: &[u8]) -> IResult<&[u8], DateOrRange> {
         },
     ))
 }
+
+// EDTF, on top of the ISO 8601 parsers above.
+
+fn edtf_year(inp: &[u8]) -> IResult<&[u8], (i32, i32)> {
+    let (rem1, pref) = opt(year_prefix)(inp)?;
+    let (rem2, y) = take_while_m_n(4, 4, |c: u8| is_digit(c) || c == b'X')(rem1)?;
+    let fill = |unspecified: i32| {
+        y.iter().fold(0, |acc, &c| {
+            acc * 10
+                + if c == b'X' {
+                    unspecified
+                } else {
+                    (c - b'0') as i32
+                }
+        })
+    };
+    let (earliest, latest) = match pref {
+        Some(b"-") => (-fill(9), -fill(0)),
+        _ => (fill(0), fill(9)),
+    };
+    Ok((rem2, (earliest, latest)))
+}
+
+fn edtf_unspecified(inp: &[u8]) -> IResult<&[u8], u32> {
+    let (rem, _) = tag("XX")(inp)?;
+    Ok((rem, 0))
+}
+
+// 21 to 24 are spring to winter, which are CSL's seasons 1 to 4.
+fn edtf_season(inp: &[u8]) -> IResult<&[u8], u32> {
+    let (rem, dig) = preceded(tag("2"), char_between('1', '4'))(inp)?;
+    Ok((rem, 12 + buf_to_u32(dig)))
+}
+
+fn edtf_month(inp: &[u8]) -> IResult<&[u8], u32> {
+    preceded(tag("-"), alt((month, edtf_season, edtf_unspecified)))(inp)
+}
+
+fn edtf_day(inp: &[u8]) -> IResult<&[u8], u32> {
+    preceded(tag("-"), alt((day, edtf_unspecified)))(inp)
+}
+
+fn edtf_qualifier(inp: &[u8]) -> IResult<&[u8], &[u8]> {
+    alt((tag("?"), tag("~"), tag("%")))(inp)
+}
+
+/// The earliest and latest dates it could be, which differ when year digits are unspecified.
+fn edtf_date(inp: &[u8]) -> IResult<&[u8], (Date, Date)> {
+    let (rem1, (earliest, latest)) = edtf_year(inp)?;
+    let (rem2, m) = opt(edtf_month)(rem1)?;
+    let (rem3, d) = opt(edtf_day)(rem2)?;
+    let (rem4, qualifier) = opt(edtf_qualifier)(rem3)?;
+    let month = m.unwrap_or(0);
+    // A day means nothing without a month, and seasons don't have days
+    let day = if month == 0 || month > 12 {
+        0
+    } else {
+        d.unwrap_or(0)
+    };
+    let date = |year| Date {
+        year,
+        month,
+        day,
+        circa: qualifier.is_some(),
+        calendar: Calendar::Gregorian,
+    };
+    Ok((rem4, (date(earliest), date(latest))))
+}
+
+fn edtf_open(inp: &[u8]) -> IResult<&[u8], (Date, Date)> {
+    let (rem, _) = opt(tag(".."))(inp)?;
+    let open = Date::new(0, 0, 0);
+    Ok((rem, (open, open)))
+}
+
+fn edtf_endpoint(inp: &[u8]) -> IResult<&[u8], (Date, Date)> {
+    alt((edtf_date, edtf_open))(inp)
+}
+
+fn edtf_range(inp: &[u8]) -> IResult<&[u8], DateOrRange> {
+    let (rem1, (earliest, latest)) = edtf_endpoint(inp)?;
+    let (rem2, end) = opt(preceded(tag("/"), edtf_endpoint))(rem1)?;
+    Ok((
+        rem2,
+        match end {
+            None if earliest == latest => DateOrRange::Single(earliest),
+            None => DateOrRange::Range(earliest, latest),
+            Some((_, end)) => DateOrRange::Range(earliest, end),
+        },
+    ))
+}
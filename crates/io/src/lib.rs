@@ -21,6 +21,7 @@ extern crate serde_derive;
 #[macro_use]
 extern crate log;
 
+mod calendar;
 mod cite;
 mod cluster;
 mod csl_json;
@@ -36,6 +37,8 @@ pub mod utils;
 pub use csl_json::NumberLike;
 pub use output::micro_html::micro_html_to_string;
 
+#[doc(inline)]
+pub use self::calendar::*;
 #[doc(inline)]
 pub use self::cite::*;
 #[doc(inline)]
@@ -77,6 +80,10 @@ pub struct IngestOptions {
 
     /// For affixes.
     pub no_parse_quotes: bool,
+
+    /// For `<text text-max-length="N">`. Truncates the ingested text to N grapheme clusters,
+    /// appending an ellipsis.
+    pub max_length: Option<u32>,
 }
 
 impl IngestOptions {
@@ -6,8 +6,14 @@ pub trait LinkId {
     const UPPER: &'static str;
     const HTTP: &'static str;
     const HTTPS: &'static str;
+    /// Older URL forms of the same resolver, e.g. `https://dx.doi.org/`.
+    const LEGACY: &'static [&'static str] = &[];
     fn trim(s: &str) -> &str {
         if s.starts_with("http") {
+            let s = Self::LEGACY
+                .iter()
+                .find(|legacy| s.starts_with(*legacy))
+                .map_or(s, |legacy| &s[legacy.len()..]);
             s.trim_start_matches(Self::HTTPS)
                 .trim_start_matches(Self::HTTP)
         } else {
@@ -27,17 +33,28 @@ pub trait LinkId {
     }
 }
 macro_rules! linkid {
-    ($vis:vis $name:ident, $lower:literal, $upper:literal, $http:literal, $https:literal) => {
+    (
+        $vis:vis $name:ident, $lower:literal, $upper:literal, $http:literal, $https:literal
+        $(, [$($legacy:literal),*])?
+    ) => {
         $vis struct $name;
         impl LinkId for $name {
             const LOWER: &'static str = $lower;
             const UPPER: &'static str = $upper;
             const HTTP: &'static str = $http;
             const HTTPS: &'static str = $https;
+            $(const LEGACY: &'static [&'static str] = &[$($legacy),*];)?
         }
     };
 }
-linkid!(pub Doi, "doi:", "DOI:", "http://doi.org/", "https://doi.org/");
+linkid!(
+    pub Doi,
+    "doi:",
+    "DOI:",
+    "http://doi.org/",
+    "https://doi.org/",
+    ["http://dx.doi.org/", "https://dx.doi.org/"]
+);
 linkid!(
     pub Pmcid,
     "pmid:",
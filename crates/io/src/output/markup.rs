@@ -24,6 +24,15 @@ use self::html::HtmlWriter;
 mod plain;
 use self::plain::PlainWriter;
 
+mod latex;
+use self::latex::LatexWriter;
+
+mod typst;
+use self::typst::TypstWriter;
+
+mod asciidoc;
+use self::asciidoc::AsciiDocWriter;
+
 mod flip_flop;
 use self::flip_flop::FlipFlopState;
 mod move_punctuation;
@@ -41,6 +50,9 @@ pub enum Markup {
     Html(FormatOptions),
     Rtf(FormatOptions),
     Plain(FormatOptions),
+    Latex(FormatOptions),
+    Typst(FormatOptions),
+    AsciiDoc(FormatOptions),
 }
 
 /// Controls how the output is formatted.
@@ -109,6 +121,15 @@ impl Markup {
     pub fn plain() -> Self {
         Markup::Plain(FormatOptions::default())
     }
+    pub fn latex() -> Self {
+        Markup::Latex(FormatOptions::default())
+    }
+    pub fn typst() -> Self {
+        Markup::Typst(FormatOptions::default())
+    }
+    pub fn asciidoc() -> Self {
+        Markup::AsciiDoc(FormatOptions::default())
+    }
 }
 
 impl Default for Markup {
@@ -136,6 +157,9 @@ impl OutputFormat for Markup {
             Markup::Html(_) => ("<div class=\"csl-bib-body\">", "</div>"),
             Markup::Rtf(_) => ("", ""),
             Markup::Plain(_) => ("", ""),
+            Markup::Latex(_) => ("", ""),
+            Markup::Typst(_) => ("", ""),
+            Markup::AsciiDoc(_) => ("", ""),
         };
         MarkupBibMeta {
             markup_pre: pre.into(),
@@ -147,6 +171,9 @@ impl OutputFormat for Markup {
     fn ingest(&self, input: &str, options: &IngestOptions) -> Self::Build {
         let mut nodes = MicroNode::parse(input, options);
         options.apply_text_case_micro(&mut nodes);
+        if let Some(max) = options.max_length {
+            MicroNode::truncate(&mut nodes, max as usize);
+        }
         if nodes.is_empty() {
             return Vec::new();
         }
@@ -283,8 +310,11 @@ impl OutputFormat for Markup {
     fn stack_preorder(&self, dest: &mut String, stack: &[FormatCmd]) {
         match *self {
             Markup::Html(options) => HtmlWriter::new(dest, options).stack_preorder(stack),
-            Markup::Rtf(options) => PlainWriter::new(dest, options).stack_preorder(stack),
+            Markup::Rtf(options) => RtfWriter::new(dest, options).stack_preorder(stack),
             Markup::Plain(options) => PlainWriter::new(dest, options).stack_preorder(stack),
+            Markup::Latex(options) => LatexWriter::new(dest, options).stack_preorder(stack),
+            Markup::Typst(options) => TypstWriter::new(dest, options).stack_preorder(stack),
+            Markup::AsciiDoc(options) => AsciiDocWriter::new(dest, options).stack_preorder(stack),
         }
     }
 
@@ -292,8 +322,11 @@ impl OutputFormat for Markup {
     fn stack_postorder(&self, dest: &mut String, stack: &[FormatCmd]) {
         match *self {
             Markup::Html(options) => HtmlWriter::new(dest, options).stack_postorder(stack),
-            Markup::Rtf(options) => PlainWriter::new(dest, options).stack_postorder(stack),
+            Markup::Rtf(options) => RtfWriter::new(dest, options).stack_postorder(stack),
             Markup::Plain(options) => PlainWriter::new(dest, options).stack_postorder(stack),
+            Markup::Latex(options) => LatexWriter::new(dest, options).stack_postorder(stack),
+            Markup::Typst(options) => TypstWriter::new(dest, options).stack_postorder(stack),
+            Markup::AsciiDoc(options) => AsciiDocWriter::new(dest, options).stack_postorder(stack),
         }
     }
 
@@ -356,6 +389,15 @@ impl Markup {
             Markup::Plain(options) => {
                 PlainWriter::new(&mut dest, options).write_inlines(&flipped, false)
             }
+            Markup::Latex(options) => {
+                LatexWriter::new(&mut dest, options).write_inlines(&flipped, false)
+            }
+            Markup::Typst(options) => {
+                TypstWriter::new(&mut dest, options).write_inlines(&flipped, false)
+            }
+            Markup::AsciiDoc(options) => {
+                AsciiDocWriter::new(&mut dest, options).write_inlines(&flipped, false)
+            }
         }
         dest
     }
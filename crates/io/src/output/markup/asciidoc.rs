@@ -0,0 +1,248 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2019 Corporation for Digital Scholarship
+
+use super::{FormatOptions, InlineElement, MarkupWriter, MaybeTrimStart};
+use crate::output::micro_html::MicroNode;
+use crate::output::FormatCmd;
+use crate::String;
+use core::fmt::{self, Write};
+use csl::Formatting;
+
+#[derive(Debug)]
+pub struct AsciiDocWriter<'a> {
+    dest: &'a mut String,
+    options: FormatOptions,
+}
+
+impl<'a> AsciiDocWriter<'a> {
+    pub fn new(dest: &'a mut String, options: FormatOptions) -> Self {
+        AsciiDocWriter { dest, options }
+    }
+}
+
+impl<'a> MarkupWriter for AsciiDocWriter<'a> {
+    fn buf(&mut self) -> &mut String {
+        self.dest
+    }
+
+    fn write_escaped(&mut self, text: &str) {
+        write!(self.dest, "{}", asciidoc_escape(text)).unwrap()
+    }
+
+    fn write_url(&mut self, url: &url::Url, trailing_slash: bool, in_attr: bool) {
+        super::write_url(
+            self.dest,
+            url,
+            trailing_slash,
+            in_attr,
+            |b, s| write!(b, "{}", AsciiDocUrlEscaper(s)),
+            |b, s| write!(b, "{}", asciidoc_escape(s)),
+        )
+        .unwrap();
+    }
+
+    fn stack_preorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter() {
+            self.dest.push_str(cmd.asciidoc_tag().0);
+        }
+    }
+
+    fn stack_postorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter().rev() {
+            if *cmd == FormatCmd::DisplayRightInline {
+                let tlen = self.dest.trim_end_matches(' ').len();
+                self.dest.truncate(tlen);
+            }
+            self.dest.push_str(cmd.asciidoc_tag().1);
+        }
+    }
+
+    fn write_micro(&mut self, micro: &MicroNode, trim_start: bool) {
+        use MicroNode::*;
+        match micro {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Quoted {
+                is_inner,
+                localized,
+                children,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_micros(children, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Formatted(nodes, cmd) => {
+                self.stack_preorder(&[*cmd][..]);
+                self.write_micros(nodes, trim_start);
+                self.stack_postorder(&[*cmd][..]);
+            }
+            NoCase(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+            NoDecor(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+        }
+    }
+
+    fn write_inline(&mut self, inline: &InlineElement, trim_start: bool) {
+        use super::InlineElement::*;
+        match inline {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Div(display, inlines) => {
+                self.stack_formats(inlines, Formatting::default(), Some(*display));
+            }
+            Micro(micros) => {
+                self.write_micros(micros, trim_start);
+            }
+            Formatted(inlines, formatting) => {
+                self.stack_formats(inlines, *formatting, None);
+            }
+            Quoted {
+                is_inner,
+                localized,
+                inlines,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_inlines(inlines, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Linked(link) => self.write_link("link:", link, "[", "]", self.options),
+        }
+    }
+}
+
+impl FormatCmd {
+    /// Opening and closing strings. Formatting without a dedicated AsciiDoc mark falls back to
+    /// a role span, which a stylesheet can target.
+    fn asciidoc_tag(self) -> (&'static str, &'static str) {
+        use super::FormatCmd::*;
+        match self {
+            DisplayBlock => (" +\n", ""),
+            DisplayIndent => (" +\n{nbsp}{nbsp}{nbsp}{nbsp}", ""),
+            DisplayLeftMargin => ("", "{nbsp}"),
+            DisplayRightInline => ("", ""),
+
+            FontStyleItalic => ("__", "__"),
+            FontStyleOblique => ("[.oblique]#", "#"),
+            FontStyleNormal => ("[.normal]#", "#"),
+
+            FontWeightBold => ("**", "**"),
+            FontWeightNormal => ("[.normal]#", "#"),
+            FontWeightLight => ("[.light]#", "#"),
+
+            FontVariantSmallCaps => ("[.smallcaps]#", "#"),
+            FontVariantNormal => ("[.normal]#", "#"),
+
+            TextDecorationUnderline => ("[.underline]#", "#"),
+            TextDecorationNone => ("", ""),
+
+            VerticalAlignmentSuperscript => ("^", "^"),
+            VerticalAlignmentSubscript => ("~", "~"),
+            VerticalAlignmentBaseline => ("", ""),
+        }
+    }
+}
+
+struct AsciiDocEscaper<'a>(&'a str);
+
+impl fmt::Display for AsciiDocEscaper<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut last = 0;
+        for (ix, c) in self.0.char_indices() {
+            // Asciidoctor's built-in attributes cover most marks; the rest are passed through.
+            let esc = match c {
+                '*' => "{asterisk}",
+                '^' => "{caret}",
+                '~' => "{tilde}",
+                '`' => "{backtick}",
+                '+' => "{plus}",
+                '[' => "{startsb}",
+                ']' => "{endsb}",
+                '\\' => "{backslash}",
+                '{' => "\\{",
+                '_' => "pass:[_]",
+                '#' => "pass:[#]",
+                _ => continue,
+            };
+            f.write_str(&self.0[last..ix])?;
+            f.write_str(esc)?;
+            last = ix + c.len_utf8();
+        }
+        f.write_str(&self.0[last..])
+    }
+}
+
+fn asciidoc_escape(s: &str) -> AsciiDocEscaper {
+    AsciiDocEscaper(s)
+}
+
+/// The target of a `link:` macro ends at whitespace or `[`.
+struct AsciiDocUrlEscaper<'a>(&'a str);
+
+impl fmt::Display for AsciiDocUrlEscaper<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '[' => f.write_str("%5B")?,
+                ']' => f.write_str("%5D")?,
+                ' ' => f.write_str("%20")?,
+                _ => f.write_char(c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn asciidoc_escape(s: &str) -> String {
+        let mut buf = String::new();
+        write!(&mut buf, "{}", super::asciidoc_escape(s)).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_asciidoc_escape() {
+        assert_eq!(
+            &asciidoc_escape("O*Brien_Smith #1 [x] ^y~ {z}"),
+            "O{asterisk}Brienpass:[_]Smith pass:[#]1 {startsb}x{endsb} {caret}y{tilde} \\{z}"
+        );
+        assert_eq!(&asciidoc_escape("Ça va"), "Ça va");
+    }
+
+    #[test]
+    fn test_asciidoc_stack() {
+        let stack_around = |stack: &[FormatCmd], content: &str| {
+            let mut dest = String::new();
+            let mut writer = AsciiDocWriter::new(&mut dest, Default::default());
+            writer.stack_preorder(stack);
+            writer.write_escaped(content);
+            writer.stack_postorder(stack);
+            dest
+        };
+        assert_eq!(
+            &stack_around(
+                &[FormatCmd::FontWeightBold, FormatCmd::FontStyleItalic],
+                "Title"
+            ),
+            "**__Title__**"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::FontVariantSmallCaps], "Star*"),
+            "[.smallcaps]#Star{asterisk}#"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::VerticalAlignmentSuperscript], "2"),
+            "^2^"
+        );
+    }
+}
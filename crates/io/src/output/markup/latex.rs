@@ -0,0 +1,272 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2019 Corporation for Digital Scholarship
+
+use super::{FormatOptions, InlineElement, MarkupWriter, MaybeTrimStart};
+use crate::output::micro_html::MicroNode;
+use crate::output::FormatCmd;
+use crate::String;
+use core::fmt::{self, Write};
+use csl::Formatting;
+
+#[derive(Debug)]
+pub struct LatexWriter<'a> {
+    dest: &'a mut String,
+    options: FormatOptions,
+}
+
+impl<'a> LatexWriter<'a> {
+    pub fn new(dest: &'a mut String, options: FormatOptions) -> Self {
+        LatexWriter { dest, options }
+    }
+}
+
+impl<'a> MarkupWriter for LatexWriter<'a> {
+    fn buf(&mut self) -> &mut String {
+        self.dest
+    }
+
+    fn write_escaped(&mut self, text: &str) {
+        write!(self.dest, "{}", latex_escape(text)).unwrap()
+    }
+
+    fn write_url(&mut self, url: &url::Url, trailing_slash: bool, in_attr: bool) {
+        super::write_url(
+            self.dest,
+            url,
+            trailing_slash,
+            in_attr,
+            |b, s| write!(b, "{}", LatexUrlEscaper(s)),
+            |b, s| write!(b, "{}", latex_escape(s)),
+        )
+        .unwrap();
+    }
+
+    fn stack_preorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter() {
+            self.dest.push_str(cmd.latex_tag().0);
+        }
+    }
+
+    fn stack_postorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter().rev() {
+            if *cmd == FormatCmd::DisplayRightInline {
+                let tlen = self.dest.trim_end_matches(' ').len();
+                self.dest.truncate(tlen);
+            }
+            self.dest.push_str(cmd.latex_tag().1);
+        }
+    }
+
+    fn write_micro(&mut self, micro: &MicroNode, trim_start: bool) {
+        use MicroNode::*;
+        match micro {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Quoted {
+                is_inner,
+                localized,
+                children,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_micros(children, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Formatted(nodes, cmd) => {
+                self.stack_preorder(&[*cmd][..]);
+                self.write_micros(nodes, trim_start);
+                self.stack_postorder(&[*cmd][..]);
+            }
+            NoCase(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+            NoDecor(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+        }
+    }
+
+    fn write_inline(&mut self, inline: &InlineElement, trim_start: bool) {
+        use super::InlineElement::*;
+        match inline {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Div(display, inlines) => {
+                self.stack_formats(inlines, Formatting::default(), Some(*display));
+            }
+            Micro(micros) => {
+                self.write_micros(micros, trim_start);
+            }
+            Formatted(inlines, formatting) => {
+                self.stack_formats(inlines, *formatting, None);
+            }
+            Quoted {
+                is_inner,
+                localized,
+                inlines,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_inlines(inlines, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Linked(link) => self.write_link(r"\href{", link, "}{", "}", self.options),
+        }
+    }
+}
+
+impl FormatCmd {
+    /// Opening and closing strings. Every command opens exactly one group, so they nest in the
+    /// same way HTML tags do.
+    fn latex_tag(self) -> (&'static str, &'static str) {
+        use super::FormatCmd::*;
+        match self {
+            DisplayBlock => ("\\par{}", "\\par{}"),
+            DisplayIndent => ("\\par{}\\hspace*{2em}", "\\par{}"),
+            DisplayLeftMargin => ("\\makebox[2em][l]{", "}"),
+            DisplayRightInline => ("{", "}"),
+
+            FontStyleItalic => ("\\emph{", "}"),
+            FontStyleOblique => ("\\textsl{", "}"),
+            FontStyleNormal => ("\\textup{", "}"),
+
+            FontWeightBold => ("\\textbf{", "}"),
+            FontWeightNormal => ("\\textmd{", "}"),
+            // Not supported by the standard font commands
+            FontWeightLight => ("\\textmd{", "}"),
+
+            FontVariantSmallCaps => ("\\textsc{", "}"),
+            FontVariantNormal => ("\\textup{", "}"),
+
+            TextDecorationUnderline => ("\\underline{", "}"),
+            TextDecorationNone => ("{", "}"),
+
+            VerticalAlignmentSuperscript => ("\\textsuperscript{", "}"),
+            VerticalAlignmentSubscript => ("\\textsubscript{", "}"),
+            VerticalAlignmentBaseline => ("{", "}"),
+        }
+    }
+}
+
+struct LatexEscaper<'a>(&'a str);
+
+impl fmt::Display for LatexEscaper<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut last = 0;
+        for (ix, c) in self.0.char_indices() {
+            let esc = match c {
+                '&' => "\\&",
+                '%' => "\\%",
+                '$' => "\\$",
+                '#' => "\\#",
+                '_' => "\\_",
+                '{' => "\\{",
+                '}' => "\\}",
+                '~' => "\\textasciitilde{}",
+                '^' => "\\textasciicircum{}",
+                '\\' => "\\textbackslash{}",
+                _ => continue,
+            };
+            f.write_str(&self.0[last..ix])?;
+            f.write_str(esc)?;
+            last = ix + c.len_utf8();
+        }
+        f.write_str(&self.0[last..])
+    }
+}
+
+fn latex_escape(s: &str) -> LatexEscaper {
+    LatexEscaper(s)
+}
+
+/// Inside the first argument of `\href`, hyperref reads the url verbatim apart from these.
+struct LatexUrlEscaper<'a>(&'a str);
+
+impl fmt::Display for LatexUrlEscaper<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            if let '%' | '#' | '\\' | '{' | '}' = c {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn latex_escape(s: &str) -> String {
+        let mut buf = String::new();
+        write!(&mut buf, "{}", super::latex_escape(s)).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_latex_escape() {
+        assert_eq!(
+            &latex_escape(r"50% of $5 & #1_a {b} ~c^ \d"),
+            r"50\% of \$5 \& \#1\_a \{b\} \textasciitilde{}c\textasciicircum{} \textbackslash{}d"
+        );
+        assert_eq!(&latex_escape("Ça va"), "Ça va");
+    }
+
+    #[test]
+    fn test_latex_url() {
+        let fmt_url = |url_str: &str, in_attr: bool| {
+            let mut dest = String::new();
+            let url = url::Url::parse(url_str).unwrap();
+            LatexWriter::new(&mut dest, Default::default()).write_url(
+                &url,
+                url_str.ends_with('/'),
+                in_attr,
+            );
+            dest
+        };
+        let url = "https://example.com/a_b?q=50%25#frag";
+        assert_eq!(
+            &fmt_url(url, true),
+            r"https://example.com/a_b?q=50\%25\#frag"
+        );
+        assert_eq!(
+            &fmt_url(url, false),
+            r"https://example.com/a\_b?q=50\%25\#frag"
+        );
+    }
+
+    #[test]
+    fn test_latex_stack() {
+        let stack_around = |stack: &[FormatCmd], content: &str| {
+            let mut dest = String::new();
+            let mut writer = LatexWriter::new(&mut dest, Default::default());
+            writer.stack_preorder(stack);
+            writer.write_escaped(content);
+            writer.stack_postorder(stack);
+            dest
+        };
+        assert_eq!(
+            &stack_around(
+                &[FormatCmd::FontWeightBold, FormatCmd::FontStyleItalic],
+                "R&D"
+            ),
+            r"\textbf{\emph{R\&D}}"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::FontVariantSmallCaps], "Smith"),
+            r"\textsc{Smith}"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::VerticalAlignmentSuperscript], "2"),
+            r"\textsuperscript{2}"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::DisplayRightInline], "Title. "),
+            r"{Title.}"
+        );
+    }
+}
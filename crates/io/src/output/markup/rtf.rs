@@ -58,6 +58,7 @@ impl<'a> MarkupWriter for RtfWriter<'a> {
                 let tlen = self.dest.trim_end_matches(' ').len();
                 self.dest.truncate(tlen);
             }
+            self.dest.push_str(cmd.rtf_close());
             self.dest.push('}');
         }
     }
@@ -135,9 +136,9 @@ impl FormatCmd {
     fn rtf_tag(self) -> &'static str {
         use super::FormatCmd::*;
         match self {
-            // TODO: RTF display commands
-            DisplayBlock => "",
-            DisplayIndent => "",
+            // Same as citeproc-js
+            DisplayBlock => "\\line{}",
+            DisplayIndent => "{\\tab}",
             DisplayLeftMargin => "",
             DisplayRightInline => "",
 
@@ -162,6 +163,17 @@ impl FormatCmd {
             VerticalAlignmentBaseline => "\\nosupersub ",
         }
     }
+
+    /// Emitted before closing the group opened with `rtf_tag`.
+    fn rtf_close(self) -> &'static str {
+        use super::FormatCmd::*;
+        match self {
+            DisplayBlock | DisplayIndent => "\\line ",
+            // Tab across to the hanging indent for the rest of the entry
+            DisplayLeftMargin => "{\\tab}",
+            _ => "",
+        }
+    }
 }
 
 use nom::{bytes::complete as nbc, character::complete::anychar, IResult, Parser};
@@ -294,7 +306,11 @@ mod test {
         let fmt_url = |url_str: &str, in_attr: bool| {
             let mut dest = String::new();
             let url = url::Url::parse(url_str).unwrap();
-            RtfWriter::new(&mut dest, Default::default()).write_url(&url, url_str.ends_with('/'), in_attr);
+            RtfWriter::new(&mut dest, Default::default()).write_url(
+                &url,
+                url_str.ends_with('/'),
+                in_attr,
+            );
             dest
         };
 
@@ -308,6 +324,38 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_rtf_display() {
+        let stack_around = |stack: &[FormatCmd], content: &str| {
+            let mut dest = String::new();
+            let mut writer = RtfWriter::new(&mut dest, Default::default());
+            writer.stack_preorder(stack);
+            writer.write_escaped(content);
+            writer.stack_postorder(stack);
+            dest
+        };
+        assert_eq!(
+            &stack_around(&[FormatCmd::DisplayLeftMargin], "[1]"),
+            r"{[1]{\tab}}"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::DisplayRightInline], "Title. "),
+            r"{Title.}"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::DisplayBlock], "Block"),
+            r"{\line{}Block\line }"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::DisplayIndent], "Indented"),
+            r"{{\tab}Indented\line }"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::FontStyleItalic], "Italic"),
+            r"{\i Italic}"
+        );
+    }
+
     /// See the main citeproc/tests/suite.rs
     #[derive(PartialEq, Eq)]
     #[doc(hidden)]
@@ -0,0 +1,264 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2019 Corporation for Digital Scholarship
+
+use super::{FormatOptions, InlineElement, MarkupWriter, MaybeTrimStart};
+use crate::output::micro_html::MicroNode;
+use crate::output::FormatCmd;
+use crate::String;
+use core::fmt::{self, Write};
+use csl::Formatting;
+
+#[derive(Debug)]
+pub struct TypstWriter<'a> {
+    dest: &'a mut String,
+    options: FormatOptions,
+}
+
+impl<'a> TypstWriter<'a> {
+    pub fn new(dest: &'a mut String, options: FormatOptions) -> Self {
+        TypstWriter { dest, options }
+    }
+}
+
+impl<'a> MarkupWriter for TypstWriter<'a> {
+    fn buf(&mut self) -> &mut String {
+        self.dest
+    }
+
+    fn write_escaped(&mut self, text: &str) {
+        write!(self.dest, "{}", typst_escape(text)).unwrap()
+    }
+
+    fn write_url(&mut self, url: &url::Url, trailing_slash: bool, in_attr: bool) {
+        super::write_url(
+            self.dest,
+            url,
+            trailing_slash,
+            in_attr,
+            |b, s| write!(b, "{}", TypstUrlEscaper(s)),
+            |b, s| write!(b, "{}", typst_escape(s)),
+        )
+        .unwrap();
+    }
+
+    fn stack_preorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter() {
+            self.dest.push_str(cmd.typst_tag().0);
+        }
+    }
+
+    fn stack_postorder(&mut self, stack: &[FormatCmd]) {
+        for cmd in stack.iter().rev() {
+            if *cmd == FormatCmd::DisplayRightInline {
+                let tlen = self.dest.trim_end_matches(' ').len();
+                self.dest.truncate(tlen);
+            }
+            self.dest.push_str(cmd.typst_tag().1);
+        }
+    }
+
+    fn write_micro(&mut self, micro: &MicroNode, trim_start: bool) {
+        use MicroNode::*;
+        match micro {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Quoted {
+                is_inner,
+                localized,
+                children,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_micros(children, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Formatted(nodes, cmd) => {
+                self.stack_preorder(&[*cmd][..]);
+                self.write_micros(nodes, trim_start);
+                self.stack_postorder(&[*cmd][..]);
+            }
+            NoCase(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+            NoDecor(inners) => {
+                self.write_micros(inners, trim_start);
+            }
+        }
+    }
+
+    fn write_inline(&mut self, inline: &InlineElement, trim_start: bool) {
+        use super::InlineElement::*;
+        match inline {
+            Text(text) => {
+                self.write_escaped(text.trim_start_if(trim_start));
+            }
+            Div(display, inlines) => {
+                self.stack_formats(inlines, Formatting::default(), Some(*display));
+            }
+            Micro(micros) => {
+                self.write_micros(micros, trim_start);
+            }
+            Formatted(inlines, formatting) => {
+                self.stack_formats(inlines, *formatting, None);
+            }
+            Quoted {
+                is_inner,
+                localized,
+                inlines,
+            } => {
+                self.write_escaped(localized.opening(*is_inner).trim_start_if(trim_start));
+                self.write_inlines(inlines, false);
+                self.write_escaped(localized.closing(*is_inner));
+            }
+            Linked(link) => self.write_link("#link(\"", link, "\")[", "]", self.options),
+        }
+    }
+}
+
+impl FormatCmd {
+    /// Opening and closing strings. Everything is a function call taking a content block; the
+    /// `*strong*` / `_emph_` shorthand does not work when it touches a word character, as in
+    /// `foo*bar*`.
+    fn typst_tag(self) -> (&'static str, &'static str) {
+        use super::FormatCmd::*;
+        match self {
+            DisplayBlock => ("#block[", "]"),
+            DisplayIndent => ("#pad(left: 2em)[", "]"),
+            DisplayLeftMargin => ("#box(width: 2em)[", "]"),
+            DisplayRightInline => ("#box[", "]"),
+
+            FontStyleItalic => ("#emph[", "]"),
+            FontStyleOblique => ("#text(style: \"oblique\")[", "]"),
+            FontStyleNormal => ("#text(style: \"normal\")[", "]"),
+
+            FontWeightBold => ("#strong[", "]"),
+            FontWeightNormal => ("#text(weight: \"regular\")[", "]"),
+            FontWeightLight => ("#text(weight: \"light\")[", "]"),
+
+            FontVariantSmallCaps => ("#smallcaps[", "]"),
+            FontVariantNormal => ("", ""),
+
+            TextDecorationUnderline => ("#underline[", "]"),
+            TextDecorationNone => ("", ""),
+
+            VerticalAlignmentSuperscript => ("#super[", "]"),
+            VerticalAlignmentSubscript => ("#sub[", "]"),
+            VerticalAlignmentBaseline => ("", ""),
+        }
+    }
+}
+
+struct TypstEscaper<'a>(&'a str);
+
+impl fmt::Display for TypstEscaper<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            // `/` is escaped so that `//` and `/*` never start a comment.
+            if matches!(
+                c,
+                '\\' | '*' | '_' | '`' | '$' | '#' | '<' | '>' | '@' | '[' | ']' | '~' | '/'
+            ) {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+fn typst_escape(s: &str) -> TypstEscaper {
+    TypstEscaper(s)
+}
+
+/// Inside the string argument of `#link`.
+struct TypstUrlEscaper<'a>(&'a str);
+
+impl fmt::Display for TypstUrlEscaper<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            if matches!(c, '"' | '\\') {
+                f.write_char('\\')?;
+            }
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn typst_escape(s: &str) -> String {
+        let mut buf = String::new();
+        write!(&mut buf, "{}", super::typst_escape(s)).unwrap();
+        buf
+    }
+
+    #[test]
+    fn test_typst_escape() {
+        assert_eq!(
+            &typst_escape(r"*a* _b_ #c $d @e [f] <g> ~h `i` \j k/l"),
+            r"\*a\* \_b\_ \#c \$d \@e \[f\] \<g\> \~h \`i\` \\j k\/l"
+        );
+        assert_eq!(&typst_escape("Ça va"), "Ça va");
+    }
+
+    #[test]
+    fn test_typst_url() {
+        let fmt_url = |url_str: &str, in_attr: bool| {
+            let mut dest = String::new();
+            let url = url::Url::parse(url_str).unwrap();
+            TypstWriter::new(&mut dest, Default::default()).write_url(
+                &url,
+                url_str.ends_with('/'),
+                in_attr,
+            );
+            dest
+        };
+        let url = "https://example.com/a_b?q=1#frag";
+        assert_eq!(&fmt_url(url, true), "https://example.com/a_b?q=1#frag");
+        assert_eq!(
+            &fmt_url(url, false),
+            r"https:\/\/example.com\/a\_b?q=1\#frag"
+        );
+    }
+
+    #[test]
+    fn test_typst_stack() {
+        let stack_around = |stack: &[FormatCmd], content: &str| {
+            let mut dest = String::new();
+            let mut writer = TypstWriter::new(&mut dest, Default::default());
+            writer.stack_preorder(stack);
+            writer.write_escaped(content);
+            writer.stack_postorder(stack);
+            dest
+        };
+        assert_eq!(
+            &stack_around(
+                &[FormatCmd::FontWeightBold, FormatCmd::FontStyleItalic],
+                "Title"
+            ),
+            "#strong[#emph[Title]]"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::FontStyleItalic], "C#_notes"),
+            r"#emph[C\#\_notes]"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::FontVariantSmallCaps], "Smith"),
+            "#smallcaps[Smith]"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::DisplayIndent], "Indented"),
+            "#pad(left: 2em)[Indented]"
+        );
+        assert_eq!(
+            &stack_around(&[FormatCmd::DisplayRightInline], "Title. "),
+            "#box[Title.]"
+        );
+    }
+}
@@ -146,6 +146,72 @@ impl HtmlReader<MicroNode> for MicroHtmlReader<'_> {
     }
 }
 
+impl MicroNode {
+    /// Cuts a tree of nodes down to at most `max` grapheme clusters of text, appending an
+    /// ellipsis if anything was removed. Formatting is never split, only emptied out.
+    pub fn truncate(nodes: &mut Vec<MicroNode>, max: usize) {
+        let mut remaining = max;
+        if truncate_inner(nodes, &mut remaining) {
+            nodes.push(MicroNode::Text("…".into()));
+        }
+    }
+}
+
+/// Returns true if anything was cut off.
+fn truncate_inner(nodes: &mut Vec<MicroNode>, remaining: &mut usize) -> bool {
+    use unic_segment::{GraphemeIndices, Graphemes};
+    let len = nodes.len();
+    for ix in 0..len {
+        let cut = match &mut nodes[ix] {
+            MicroNode::Text(text) => match GraphemeIndices::new(text).nth(*remaining) {
+                Some((byte_ix, _)) => {
+                    text.truncate(byte_ix);
+                    let trimmed = text.trim_end().len();
+                    text.truncate(trimmed);
+                    *remaining = 0;
+                    true
+                }
+                None => {
+                    *remaining -= Graphemes::new(text).count();
+                    false
+                }
+            },
+            MicroNode::Formatted(children, _)
+            | MicroNode::NoCase(children)
+            | MicroNode::NoDecor(children)
+            | MicroNode::Quoted { children, .. } => truncate_inner(children, remaining),
+        };
+        if cut || (*remaining == 0 && ix + 1 < len) {
+            nodes.truncate(ix + 1);
+            return true;
+        }
+    }
+    false
+}
+
+#[test]
+fn test_truncate() {
+    let mut nodes = MicroNode::parse("Hello <i>wide</i> world", &Default::default());
+    MicroNode::truncate(&mut nodes, 8);
+    use FormatCmd::*;
+    use MicroNode::*;
+    assert_eq!(
+        nodes,
+        &[
+            Text("Hello ".into()),
+            Formatted(vec![Text("wi".into())], FontStyleItalic),
+            Text("…".into()),
+        ]
+    );
+    let mut nodes = MicroNode::parse("Short", &Default::default());
+    MicroNode::truncate(&mut nodes, 8);
+    assert_eq!(nodes, &[Text("Short".into())]);
+    // e + combining acute accent is one grapheme
+    let mut nodes = MicroNode::parse("Cafe\u{301} au lait", &Default::default());
+    MicroNode::truncate(&mut nodes, 4);
+    assert_eq!(nodes, &[Text("Cafe\u{301}".into()), Text("…".into())]);
+}
+
 #[test]
 fn test_sanitize() {
     let fragment =
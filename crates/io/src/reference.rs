@@ -8,7 +8,7 @@
 // std::collections::HashMap does
 extern crate fnv;
 
-use fnv::FnvHashMap;
+use fnv::{FnvHashMap, FnvHashSet};
 
 use super::date::DateOrRange;
 use super::names::Name;
@@ -26,10 +26,19 @@ pub struct Reference {
     // and writing a Fn(Variable::Xxx) -> CslJson.xxx; would be O(n)
     // whereas these hashes are essentially O(1) for our purposes
     pub ordinary: FnvHashMap<Variable, String>,
+    // ordinary variables given as an array of strings in CSL-JSON, like several `note` lines.
+    // `ordinary` holds these values joined with ", " for sorting and conditions.
+    pub multi: FnvHashMap<Variable, Vec<String>>,
     // we do the conversion on the input side, so is-numeric is just Result::ok
     pub number: FnvHashMap<NumberVariable, NumberLike>,
     pub name: FnvHashMap<NameVariable, Vec<Name>>,
     pub date: FnvHashMap<DateVariable, DateOrRange>,
+    // translations of ordinary variables, from CSL-M's `multi: { _keys: { title: { en: ... } } }`
+    pub variants: FnvHashMap<Variable, Vec<(Lang, String)>>,
+    // not part of CSL-JSON; set by the host program, for `<if flag="...">`
+    pub flags: FnvHashSet<String>,
+    // keys in the input that are not CSL variables; they are ignored, but reported as warnings
+    pub unknown_variables: Vec<String>,
 }
 
 impl Reference {
@@ -39,9 +48,28 @@ impl Reference {
             csl_type,
             language: None,
             ordinary: FnvHashMap::default(),
+            multi: FnvHashMap::default(),
             number: FnvHashMap::default(),
             name: FnvHashMap::default(),
             date: FnvHashMap::default(),
+            variants: FnvHashMap::default(),
+            flags: FnvHashSet::default(),
+            unknown_variables: Vec::new(),
         }
     }
+
+    /// The variant of an ordinary variable in `lang`. An exact match wins; otherwise any variant
+    /// in the same language, ignoring the region, will do.
+    pub fn variant(&self, var: Variable, lang: &Lang) -> Option<&str> {
+        let variants = self.variants.get(&var)?;
+        let same_language = |other: &Lang| match (lang, other) {
+            (Lang::Iso(a, _), Lang::Iso(b, _)) => a == b,
+            _ => lang == other,
+        };
+        variants
+            .iter()
+            .find(|(l, _)| l == lang)
+            .or_else(|| variants.iter().find(|(l, _)| same_language(l)))
+            .map(|(_, value)| value.as_str())
+    }
 }
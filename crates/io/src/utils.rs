@@ -6,6 +6,8 @@
 
 use crate::String;
 
+/// Converts a year-suffix number to spreadsheet-column letters: 1 → a, 26 → z, 27 → aa.
+/// Zero produces an empty string.
 pub fn to_bijective_base_26(int: u32) -> String {
     let mut n = int;
     let mut s = String::new();
@@ -95,3 +97,25 @@ where
         result
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::to_bijective_base_26 as b26;
+
+    #[test]
+    fn bijective_base_26() {
+        assert_eq!(b26(0), "");
+        assert_eq!(b26(1), "a");
+        assert_eq!(b26(2), "b");
+        assert_eq!(b26(26), "z");
+        assert_eq!(b26(27), "aa");
+        assert_eq!(b26(28), "ab");
+        assert_eq!(b26(52), "az");
+        assert_eq!(b26(53), "ba");
+        assert_eq!(b26(702), "zz");
+        assert_eq!(b26(703), "aaa");
+        assert_eq!(b26(704), "aab");
+        assert_eq!(b26(18278), "zzz");
+        assert_eq!(b26(18279), "aaaa");
+    }
+}
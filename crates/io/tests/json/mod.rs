@@ -14,7 +14,7 @@ use schema::{Primitive, SchemaNode};
 use serde_json::json;
 mod var {
     pub use csl::DateVariable::Issued;
-    pub use csl::Variable::{Title, TitleShort};
+    pub use csl::Variable::{Note, Title, TitleShort};
     pub use csl::{DateVariable, NameVariable, NumberVariable, Variable};
 }
 
@@ -158,6 +158,54 @@ test_parse!(
     }
 );
 
+test_parse!(
+    season_numeric,
+    r#" { "id": 1, "issued": { "date-parts": [[2000]], "season": 2 } } "#,
+    |r: Reference| {
+        assert_key!(
+            r.date,
+            Issued,
+            Some(DateOrRange::Single(Date::new(2000, 14, 0)))
+        );
+    }
+);
+test_equiv_all!(
+    season_strings,
+    r#"[
+    { "id": 1, "issued": { "date-parts": [[2000]], "season": 3 } },
+    { "id": 1, "issued": { "date-parts": [[2000]], "season": "3" } },
+    { "id": 1, "issued": { "date-parts": [[2000]], "season": 23 } },
+    { "id": 1, "issued": { "date-parts": [[2000]], "season": "Autumn" } },
+    { "id": 1, "issued": { "date-parts": [[2000]], "season": "fall" } }
+]"#
+);
+
+test_parse!(
+    season_unrecognised_string,
+    r#" { "id": 1, "issued": { "date-parts": [[2000]], "season": "Trinity" } } "#,
+    |r: Reference| {
+        assert_key!(
+            r.date,
+            Issued,
+            Some(DateOrRange::Literal {
+                literal: "Trinity 2000".into(),
+                circa: false,
+            })
+        );
+    }
+);
+
+test_equiv!(
+    edtf_after_date_parts,
+    r#" { "id": 1, "issued": { "date-parts": [[2000, 5]], "edtf": "1999" } } "#
+    => r#" { "id": 1, "issued": { "date-parts": [[2000, 5]] } } "#
+);
+test_equiv!(
+    edtf_after_raw,
+    r#" { "id": 1, "issued": { "raw": "2000-05", "edtf": "1999" } } "#
+    => r#" { "id": 1, "issued": { "date-parts": [[2000, 5]] } } "#
+);
+
 test_parse!(
     title_short,
     r#" { "id": 1, "title-short": "title" } "#,
@@ -165,6 +213,18 @@ test_parse!(
         assert_key_deref!(r.ordinary, TitleShort, Some("title"));
     }
 );
+test_parse!(
+    multi_valued_note,
+    r#" { "id": 1, "note": ["first", "second"] } "#,
+    |r: Reference| {
+        assert_key_deref!(r.ordinary, Note, Some("first, second"));
+        assert_eq!(
+            r.multi.get(&Note),
+            Some(&vec!["first".to_owned(), "second".to_owned()])
+        );
+    }
+);
+
 test_equiv_all!(
     title_short_shortTitle,
     r#"[
@@ -181,10 +241,35 @@ test_equiv_all!(
 ]"#
 );
 
-test_equiv!(ignore_unknown_keys, r#" { "id": 1, "will_never_be_added_to_csl_unknown": "title" } "# => EMPTY);
-test_equiv!(ignore_unknown_weird_keys, r#" { "id": 1, "with\"quote": "title" } "# => EMPTY);
-test_equiv!(ignore_unknown_keys_weird_data, r#" { "id": 1, "asdklfjhhjkl": { "completely": "unrecognizable" } } "# => EMPTY);
-test_equiv!(ignore_unknown_weird_keys_weird_data, r#" { "id": 1, "\"\"\"": { "completely": -0.9999 } } "# => EMPTY);
+/// Unknown keys are dropped, and only remembered by name.
+fn ignored_as_unknown(mut r: Reference, key: &str) {
+    assert_eq!(r.unknown_variables, vec![key.to_owned()]);
+    r.unknown_variables.clear();
+    let empty: Reference = serde_json::from_str(EMPTY).unwrap();
+    assert_eq!(r, empty);
+}
+
+test_parse!(
+    ignore_unknown_keys,
+    r#" { "id": 1, "will_never_be_added_to_csl_unknown": "title" } "#,
+    |r| ignored_as_unknown(r, "will_never_be_added_to_csl_unknown")
+);
+test_parse!(
+    ignore_unknown_weird_keys,
+    r#" { "id": 1, "with\"quote": "title" } "#,
+    |r| ignored_as_unknown(r, "with\"quote")
+);
+test_parse!(
+    ignore_unknown_keys_weird_data,
+    r#" { "id": 1, "asdklfjhhjkl": { "completely": "unrecognizable" } } "#,
+    |r| ignored_as_unknown(r, "asdklfjhhjkl")
+);
+test_parse!(
+    ignore_unknown_weird_keys_weird_data,
+    r#" { "id": 1, "\"\"\"": { "completely": -0.9999 } } "#,
+    |r| ignored_as_unknown(r, "\"\"\"")
+);
+test_equiv!(ignore_non_variable_fields, r#" { "id": 1, "categories": ["a"], "custom": { "x": 1 } } "# => EMPTY);
 
 test_parse!(
     duplicate_keys_ok,
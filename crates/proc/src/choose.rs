@@ -269,6 +269,7 @@ where
             Cond::HasYearOnly(dvar) => checker.has_year_only(*dvar),
             Cond::HasMonthOrSeason(dvar) => checker.has_month_or_season(*dvar),
             Cond::HasDay(dvar) => checker.has_day(*dvar),
+            Cond::Flag(flag) => checker.has_flag(flag),
 
             // Not implemented
             Cond::Context(_)
@@ -373,4 +374,8 @@ pub trait CondChecker {
         self.get_date(dvar)
             .map_or(false, |dor| dor.is_uncertain_date())
     }
+    /// Flags set on the reference by the host program, not part of CSL-JSON
+    fn has_flag(&self, _flag: &str) -> bool {
+        false
+    }
 }
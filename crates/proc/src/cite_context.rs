@@ -95,6 +95,15 @@ impl<'c, O: OutputFormat, I: OutputFormat> CiteContext<'c, O, I> {
             year_suffix: self.year_suffix,
         }
     }
+
+    /// The cite's locators after the first, if the style renders them with the
+    /// `multiple-locators` feature.
+    pub fn extra_locators(&self) -> &[Locator] {
+        match self.cite.locators {
+            Some(ref locators) if self.style.features.multiple_locators => locators.rest(),
+            _ => &[],
+        }
+    }
 }
 
 impl<'a, O: OutputFormat, I: OutputFormat> RenderContext for CiteContext<'a, O, I> {
@@ -135,6 +144,17 @@ impl<'a, O: OutputFormat, I: OutputFormat> RenderContext for CiteContext<'a, O,
     }
 }
 
+/// https://docs.citationstyles.org/en/stable/specification.html#non-english-items
+pub(crate) fn is_english(style: &Style, item_lang: Option<&Lang>) -> bool {
+    // Bit messy but matches the spec wording
+    // If a style doesn't have a default, it's en-US, which is English.
+    let default_is_english = style
+        .default_locale
+        .as_ref()
+        .map_or(true, |x| x.is_english());
+    item_lang.map_or(default_is_english, |l| l.is_english())
+}
+
 pub trait RenderContext {
     fn style(&self) -> &Style;
     fn reference(&self) -> &Reference;
@@ -168,6 +188,27 @@ pub trait RenderContext {
         }
     }
 
+    /// `get_ordinary`, but preferring the reference's variant in `lang` if it has one.
+    fn get_ordinary_variant(
+        &self,
+        var: Variable,
+        form: VariableForm,
+        lang: Option<&Lang>,
+    ) -> Option<Cow<'_, str>> {
+        lang.and_then(|lang| self.reference().variant(var, lang))
+            .map(Cow::Borrowed)
+            .or_else(|| self.get_ordinary(var, form))
+    }
+
+    /// Repeatable variables given as several values. Only for the long form, as the short forms
+    /// fall back to other variables.
+    fn get_ordinary_multi(&self, var: Variable, form: VariableForm) -> Option<&[String]> {
+        match form {
+            VariableForm::Long => self.reference().multi.get(&var).map(|x| x.as_slice()),
+            _ => None,
+        }
+    }
+
     /// Just makes sure NameVariable::Dummy can't produce anything
     fn get_name(&self, var: NameVariable) -> Option<&[Name]> {
         match var {
@@ -180,7 +221,7 @@ pub trait RenderContext {
 // Implemented here privately so we don't use it by mistake.
 // It's meant to be used only by CiteContext::has_variable, which wraps it and prevents
 // testing variables that only exist on the Cite.
-fn ref_has_variable(refr: &Reference, var: AnyVariable) -> bool {
+pub(crate) fn ref_has_variable(refr: &Reference, var: AnyVariable) -> bool {
     match var {
         AnyVariable::Ordinary(v) => refr.ordinary.contains_key(&v),
         AnyVariable::Number(v) => refr.number.contains_key(&v),
@@ -207,6 +248,8 @@ where
             },
             // Generated on demand
             AnyVariable::Ordinary(Variable::CitationLabel) => true,
+            // Only known once the year suffix pass has run
+            AnyVariable::Ordinary(Variable::YearSuffix) => self.year_suffix.is_some(),
             _ => ref_has_variable(self.reference, var),
         }
     }
@@ -257,4 +300,7 @@ where
     fn features(&self) -> &Features {
         &self.style.features
     }
+    fn has_flag(&self, flag: &str) -> bool {
+        self.reference.flags.contains(flag)
+    }
 }
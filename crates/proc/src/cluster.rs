@@ -26,11 +26,85 @@ pub fn built_cluster_before_output(
     cluster_id: ClusterId,
     fmt: &Markup,
 ) -> MarkupBuild {
-    let cite_ids = if let Some(x) = db.cluster_cites_sorted(cluster_id) {
-        x
-    } else {
-        return fmt.plain("");
+    let collapsing = db.style().citation.group_collapsing();
+    built_cluster_with_collapsing(db, cluster_id, fmt, collapsing)
+}
+
+/// Whether applying `collapse` to a cluster would change its output, compared to rendering it
+/// with no cite grouping or collapsing at all. The style's own collapse setting is ignored.
+pub fn would_collapse_change(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    collapse: Collapse,
+) -> bool {
+    let fmt = db.get_formatter();
+    let plain = built_cluster_with_collapsing(db, cluster_id, &fmt, None);
+    let collapsed = built_cluster_with_collapsing(db, cluster_id, &fmt, Some(Some(collapse)));
+    fmt.output(plain, false) != fmt.output(collapsed, false)
+}
+
+/// Like `built_cluster_before_output`, but also returns a copy of the IR the cluster was rendered
+/// from. The arena reflects the state after disambiguation and cite grouping/collapsing: the
+/// returned root is a layout `IR::Seq` with one child per cite that was written to the output.
+pub fn built_cluster_with_ir_before_output(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    fmt: &Markup,
+) -> (MarkupBuild, IrArena, NodeId) {
+    let style = db.style();
+    let layout = &style.citation.layout;
+    let mut arena = IrArena::new();
+    let root = arena.new_node((
+        IR::Seq(IrSeq {
+            delimiter: layout.delimiter.clone(),
+            affixes: layout.affixes.clone(),
+            formatting: layout.formatting,
+            is_layout: true,
+            ..Default::default()
+        }),
+        GroupVars::Important,
+    ));
+    let mut tree = IrTree::new(root, arena);
+    let collapsing = style.citation.group_collapsing();
+    let build = match cites_in_cluster(db, cluster_id, fmt, collapsing) {
+        Some(irs) => {
+            for cite in &irs {
+                if cite.destination == WhichStream::Nowhere {
+                    continue;
+                }
+                if let Some(copy) = tree.extend(cite.gen4.tree_ref()) {
+                    root.append(copy, &mut tree.arena);
+                }
+            }
+            render_cites(db, cluster_id, fmt, irs)
+        }
+        None => fmt.plain(""),
     };
+    (build, tree.arena, tree.root)
+}
+
+/// `collapsing` is in the form returned by `Citation::group_collapsing`.
+fn built_cluster_with_collapsing(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    fmt: &Markup,
+    collapsing: Option<Option<Collapse>>,
+) -> MarkupBuild {
+    match cites_in_cluster(db, cluster_id, fmt, collapsing) {
+        Some(irs) => render_cites(db, cluster_id, fmt, irs),
+        None => fmt.plain(""),
+    }
+}
+
+/// Gathers the disambiguated cites of a cluster and applies cluster/cite modes, grouping and
+/// collapsing to them. `None` if the cluster doesn't exist.
+fn cites_in_cluster(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    fmt: &Markup,
+    collapsing: Option<Option<Collapse>>,
+) -> Option<Vec<CiteInCluster<Markup>>> {
+    let cite_ids = db.cluster_cites_sorted(cluster_id)?;
     let style = db.style();
     let sorted_refs_arc = db.sorted_refs();
     let mut irs: Vec<_> = cite_ids
@@ -45,7 +119,7 @@ pub fn built_cluster_before_output(
         })
         .collect();
 
-    if let Some(maybe_collapse) = style.citation.group_collapsing() {
+    if let Some(maybe_collapse) = collapsing {
         group_by_name(&fmt, maybe_collapse, &mut irs);
     }
 
@@ -64,7 +138,7 @@ pub fn built_cluster_before_output(
         transforms::apply_cite_modes(db, &mut irs, fmt);
     }
 
-    if let Some(Some(collapse)) = style.citation.group_collapsing() {
+    if let Some(Some(collapse)) = collapsing {
         collapse_cites(&fmt, collapse, &mut irs);
     }
 
@@ -86,9 +160,21 @@ pub fn built_cluster_before_output(
             }
         }
     }
+    Some(irs)
+}
 
+fn render_cites(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+    fmt: &Markup,
+    irs: Vec<CiteInCluster<Markup>>,
+) -> MarkupBuild {
+    let style = db.style();
+    let cluster_mode = db.cluster_mode(cluster_id);
     let default_locale = db.default_locale();
-    let citation_delims = layout::LayoutDelimiters::from_citation(&style.citation);
+    let lang = cluster_lang(db, &irs);
+    let citation_delims =
+        layout::LayoutDelimiters::from_citation(&style.citation, lang.as_ref(), &default_locale);
     let intext_delimiters = layout::LayoutDelimiters::from_intext(
         style.intext.as_ref(),
         &style.citation,
@@ -132,16 +218,7 @@ pub fn built_cluster_before_output(
 
     intext_stream.write_interspersed(intext_authors, DelimKind::Layout);
 
-    for cite in &irs {
-        match cite.destination {
-            WhichStream::Nowhere | WhichStream::MainToIntext { .. } => {
-                continue;
-            }
-            _ => {
-                citation_stream.write_flat(cite, None);
-            }
-        }
-    }
+    write_citation_stream(&mut citation_stream, &irs);
 
     let citation_final = citation_stream.finish();
     let intext_final = intext_stream.finish();
@@ -167,6 +244,49 @@ pub fn built_cluster_before_output(
     fmt.seq(seq)
 }
 
+/// The language of a cluster's first cite, whose layout supplies the cluster's delimiter and
+/// affixes when the style has locale layouts.
+fn cluster_lang(db: &dyn IrDatabase, irs: &[CiteInCluster<Markup>]) -> Option<csl::Lang> {
+    if db.style().citation.locale_layouts.is_empty() {
+        return None;
+    }
+    let first = irs.first()?;
+    db.reference(first.cite.ref_id.clone())?.language.clone()
+}
+
+fn write_citation_stream(stream: &mut layout::LayoutStream, irs: &[CiteInCluster<Markup>]) {
+    for cite in irs {
+        match cite.destination {
+            WhichStream::Nowhere | WhichStream::MainToIntext { .. } => {
+                continue;
+            }
+            _ => {
+                stream.write_flat(cite, None);
+            }
+        }
+    }
+}
+
+/// The delimiters written between the cites of a cluster's `<citation>` output, in order, after
+/// grouping and collapsing. These can differ from the layout delimiter, e.g. where
+/// `after-collapse-delimiter` or a collapsed range applies. Empty if the cluster doesn't exist.
+pub fn cluster_delimiters(db: &dyn IrDatabase, cluster_id: ClusterId) -> Vec<SmartString> {
+    let fmt = db.get_formatter();
+    let style = db.style();
+    let collapsing = style.citation.group_collapsing();
+    let irs = match cites_in_cluster(db, cluster_id, &fmt, collapsing) {
+        Some(irs) => irs,
+        None => return Vec::new(),
+    };
+    let default_locale = db.default_locale();
+    let lang = cluster_lang(db, &irs);
+    let delims =
+        layout::LayoutDelimiters::from_citation(&style.citation, lang.as_ref(), &default_locale);
+    let mut stream = layout::LayoutStream::new(irs.len() * 2, delims, &fmt);
+    write_citation_stream(&mut stream, &irs);
+    stream.finish_delimiters()
+}
+
 /// A wrapper for Option where `a == b` evaluates to false if either is empty
 ///
 /// Implements PartialEq, but does not implement Eq, of course.
@@ -508,25 +628,40 @@ pub(crate) fn collapse_cites<O: OutputFormat<Output = SmartString>>(
                         Some(DelimKind::CiteGroup)
                     }
                 };
-                match name_run {
-                    [] => log::warn!("run of same name should never be empty"),
-                    [_single] => {}
-                    [head, middle @ .., last] => {
-                        head.own_delimiter = delim_for_cite(
-                            head,
-                            middle.get(0).map_or(false, |x| x.has_locator_or_affixes),
-                        );
-                        let mut middle_iter = middle.iter_mut().peekable();
-                        while let Some(cite) = middle_iter.next() {
-                            suppress_names(cite);
-                            let next_affixed = middle_iter
-                                .peek()
-                                .map(|x| &**x)
-                                .or(by_name.peek().and_then(|x| x.first()))
-                                .map_or(false, |x| x.has_locator_or_affixes);
-                            cite.own_delimiter = delim_for_cite(cite, next_affixed);
+                // A cite with its own prefix or suffix is written out in full, names and all,
+                // and splits the run of same-name cites around it.
+                let mut sub_runs =
+                    group_by_mut(name_run, |a, b| !a.cite.has_affix() && !b.cite.has_affix())
+                        .peekable();
+                while let Some(sub_run) = sub_runs.next() {
+                    let more_follow = sub_runs.peek().is_some();
+                    match sub_run {
+                        [] => log::warn!("run of same name should never be empty"),
+                        [single] => {
+                            if more_follow {
+                                single.own_delimiter = Some(DelimKind::AfterCollapse);
+                            }
+                        }
+                        [head, middle @ .., last] => {
+                            head.own_delimiter = delim_for_cite(
+                                head,
+                                middle.get(0).map_or(false, |x| x.has_locator_or_affixes),
+                            );
+                            let mut middle_iter = middle.iter_mut().peekable();
+                            while let Some(cite) = middle_iter.next() {
+                                suppress_names(cite);
+                                let next_affixed = middle_iter
+                                    .peek()
+                                    .map(|x| &**x)
+                                    .or(by_name.peek().and_then(|x| x.first()))
+                                    .map_or(false, |x| x.has_locator_or_affixes);
+                                cite.own_delimiter = delim_for_cite(cite, next_affixed);
+                            }
+                            suppress_names(last);
+                            if more_follow {
+                                last.own_delimiter = Some(DelimKind::AfterCollapse);
+                            }
                         }
-                        suppress_names(last);
                     }
                 }
             }
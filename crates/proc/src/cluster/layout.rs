@@ -151,6 +151,21 @@ impl<'a> LayoutStream<'a> {
         }
     }
 
+    /// The delimiters `finish` would write between cites, in order. Empty delimiters are
+    /// included as empty strings.
+    pub(crate) fn finish_delimiters(mut self) -> Vec<SmartString> {
+        self.pop_delim();
+        self.overwrite_and_position();
+        let delimiters = &self.delimiters;
+        self.chunks
+            .iter()
+            .filter_map(|x| match x {
+                Chunk::Delim(d) => Some(delimiters.delim(*d).unwrap_or("").into()),
+                _ => None,
+            })
+            .collect()
+    }
+
     pub(crate) fn finish(mut self) -> Option<MarkupBuild> {
         self.pop_delim();
         self.overwrite_and_position();
@@ -206,6 +221,7 @@ pub(crate) struct LayoutDelimiters<'a> {
     pub year_suffix: &'a str,
     pub after_collapse: &'a str,
     pub layout_delim: &'a str,
+    pub range: &'a str,
     pub affixes: Option<&'a Affixes>,
     pub formatting: Option<Formatting>,
     pub and_last_delimiter: Option<SmartString>,
@@ -228,15 +244,21 @@ impl<'a> LayoutDelimiters<'a> {
             DelimKind::AfterCollapse => self.after_collapse,
             DelimKind::YearSuffix => self.year_suffix,
             DelimKind::Layout => self.layout_delim,
-            DelimKind::Range => "\u{2013}",
+            DelimKind::Range => self.range,
             // should not have to observe None here, simply don't write any Ands until you are sure
             // you have and_last_delimiter
             DelimKind::And => return self.and_last_delimiter.as_opt_str(),
         })
         .filter(|x| !x.is_empty())
     }
-    pub(crate) fn from_citation(citation: &'a csl::Citation) -> Self {
-        let layout_opt = citation.layout.delimiter.as_opt_str();
+    /// `lang` picks one of the citation's locale layouts, see `csl::Citation::layout_for`.
+    pub(crate) fn from_citation(
+        citation: &'a csl::Citation,
+        lang: Option<&csl::Lang>,
+        merged_locale: &'a csl::Locale,
+    ) -> Self {
+        let layout = citation.layout_for(lang);
+        let layout_opt = layout.delimiter.as_opt_str();
         let cite_group = citation.cite_group_delimiter.as_opt_str().unwrap_or(", ");
         let year_suffix = citation
             .year_suffix_delimiter
@@ -249,13 +271,15 @@ impl<'a> LayoutDelimiters<'a> {
             .or(layout_opt)
             .unwrap_or("");
         let layout_delim = layout_opt.unwrap_or("");
-        let affixes = citation.layout.affixes.as_ref();
-        let formatting = citation.layout.formatting.clone();
+        let range = crate::number::get_range_delimiter(merged_locale);
+        let affixes = layout.affixes.as_ref();
+        let formatting = layout.formatting.clone();
         Self {
             cite_group,
             year_suffix,
             after_collapse,
             layout_delim,
+            range,
             affixes,
             formatting,
             and_last_delimiter: None,
@@ -266,7 +290,7 @@ impl<'a> LayoutDelimiters<'a> {
         citation: &'a csl::Citation,
         merged_locale: &'a csl::Locale,
     ) -> Self {
-        let mut citation = LayoutDelimiters::from_citation(citation);
+        let mut citation = LayoutDelimiters::from_citation(citation, None, merged_locale);
         citation.formatting = None;
         citation.affixes = None;
         if let Some(intext_el) = intext_el {
@@ -297,6 +321,7 @@ impl<'a> LayoutDelimiters<'a> {
                 year_suffix: citation.year_suffix,
                 after_collapse,
                 layout_delim,
+                range: citation.range,
                 affixes,
                 formatting,
                 and_last_delimiter,
@@ -7,7 +7,7 @@
 use crate::prelude::*;
 
 use crate::number::render_ordinal;
-use citeproc_io::{lazy, Date, DateOrRange};
+use citeproc_io::{lazy, Calendar, Date, DateOrRange};
 use csl::terms::*;
 use csl::LocaleDate;
 #[cfg(test)]
@@ -463,7 +463,16 @@ fn build_parts<'c, O: OutputFormat, I: OutputFormat>(
                     if sorting || matches {
                         let is_filtered =
                             !matches && ctx.sort_key().map_or(false, |k| k.is_macro());
-                        dp_render_either(var, dp, ctx.clone(), arena, single, false, is_filtered)
+                        dp_render_either(
+                            var,
+                            dp,
+                            ctx.clone(),
+                            arena,
+                            single,
+                            false,
+                            is_filtered,
+                            false,
+                        )
                     } else {
                         None
                     }
@@ -494,6 +503,11 @@ fn build_parts<'c, O: OutputFormat, I: OutputFormat>(
                 return Some(builder.into_either(fmt));
             }
             let tokens = DateRangePartsIter::new(gen_date.sorting, parts, selector, first, second);
+            // If both years are rendered and are in the same era, the era term only goes on the
+            // second one (50–79AD, but 50BC–50AD).
+            let shared_era = first.year != second.year
+                && era_term(first.year).is_some()
+                && era_term(first.year) == era_term(second.year);
             let mut builder = PartBuilder::new(gen_date, len_hint);
             let mut seen_one = false;
             let mut last_rdel = false;
@@ -511,6 +525,7 @@ fn build_parts<'c, O: OutputFormat, I: OutputFormat>(
                             builder.push_either(arena, Either::Build(Some(fmt.plain(&delim))))
                         }
                         last_rdel = false;
+                        let omit_era = shared_era && std::ptr::eq(date, first);
                         if let Some((_form, either)) = dp_render_either(
                             var,
                             part,
@@ -519,6 +534,7 @@ fn build_parts<'c, O: OutputFormat, I: OutputFormat>(
                             date,
                             is_max_diff,
                             false,
+                            omit_era,
                         ) {
                             builder.push_either(arena, either);
                         }
@@ -716,13 +732,14 @@ fn dp_render_either<'c, O: OutputFormat, I: OutputFormat>(
     date: &Date,
     is_max_diff: bool,
     is_filtered: bool,
+    omit_era: bool,
 ) -> Option<(DatePartForm, Either<O>)> {
     let fmt = ctx.format();
     if let Some(key) = ctx.sort_key() {
         let string = dp_render_sort_string(part, date, key, is_filtered);
         return string.map(|s| (part.form, Either::Build(Some(fmt.text_node(s, None)))));
     }
-    let string = dp_render_string(part, &ctx, date);
+    let string = dp_render_string(part, &ctx, date, omit_era);
     string
         .map(|s| {
             let mut affixes = part.affixes.clone();
@@ -808,7 +825,18 @@ fn dp_render_sort_string(
     }
 }
 
-fn render_year(year: i32, form: YearForm, locale: &Locale) -> SmartString {
+/// The era term a year is rendered with, if any. Four-digit CE years don't need one.
+fn era_term(year: i32) -> Option<MiscTerm> {
+    if year < 0 {
+        Some(MiscTerm::Bc)
+    } else if year > 0 && year < 1000 {
+        Some(MiscTerm::Ad)
+    } else {
+        None
+    }
+}
+
+fn render_year(year: i32, form: YearForm, locale: &Locale, omit_era: bool) -> SmartString {
     let mut s = SmartString::new();
     if year == 0 {
         // Open year range
@@ -819,19 +847,16 @@ fn render_year(year: i32, form: YearForm, locale: &Locale) -> SmartString {
         (YearForm::Short, true) => write!(s, "{:02}", year.abs() % 100).unwrap(),
         (YearForm::Long, _) | (YearForm::Short, false) => write!(s, "{}", year.abs()).unwrap(),
     }
-    if year < 0 {
-        let sel = SimpleTermSelector::Misc(MiscTerm::Bc, TermFormExtended::Long);
+    if omit_era {
+        return s;
+    }
+    if let Some(era) = era_term(year) {
+        let sel = SimpleTermSelector::Misc(era, TermFormExtended::Long);
         let sel = TextTermSelector::Simple(sel);
-        if let Some(bc) = locale.get_text_term(sel, false) {
-            s.push_str(bc);
-        } else {
+        if let Some(term) = locale.get_text_term(sel, false) {
+            s.push_str(term);
+        } else if era == MiscTerm::Bc {
             s.push_str("BC");
-        }
-    } else if year < 1000 {
-        let sel = SimpleTermSelector::Misc(MiscTerm::Ad, TermFormExtended::Long);
-        let sel = TextTermSelector::Simple(sel);
-        if let Some(ad) = locale.get_text_term(sel, false) {
-            s.push_str(ad);
         } else {
             s.push_str("AD");
         }
@@ -839,14 +864,42 @@ fn render_year(year: i32, form: YearForm, locale: &Locale) -> SmartString {
     s
 }
 
+#[test]
+fn test_render_year_short() {
+    let locale = Locale::default();
+    let short = |year| render_year(year, YearForm::Short, &locale, false);
+    assert_eq!(short(1999), "99");
+    assert_eq!(short(2007), "07");
+    // Years without four digits are never shortened, and keep their era
+    assert_eq!(short(99), "99AD");
+    assert_eq!(short(-44), "44BC");
+    assert_eq!(short(-1999), "1999BC");
+}
+
+/// A year in the calendar it was given in, with that calendar's era. CSL has no terms for these
+/// eras, so they are not localized.
+fn render_calendar_year(year: i32, calendar: Calendar) -> SmartString {
+    let in_calendar = calendar.calendar_year(year);
+    match calendar {
+        Calendar::Japanese(era) if in_calendar == 1 => smart_format!("{}元年", era.name()),
+        Calendar::Japanese(era) => smart_format!("{}{}年", era.name(), in_calendar),
+        Calendar::Hebrew => smart_format!("{} AM", in_calendar),
+        Calendar::Gregorian => smart_format!("{}", year),
+    }
+}
+
 fn dp_render_string<'c, O: OutputFormat, I: OutputFormat>(
     part: &DatePart,
     ctx: &GenericContext<'c, O, I>,
     date: &Date,
+    omit_era: bool,
 ) -> Option<SmartString> {
     let locale = ctx.locale();
     match part.form {
-        DatePartForm::Year(form) => Some(render_year(date.year, form, ctx.locale())),
+        DatePartForm::Year(_) if date.calendar != Calendar::Gregorian => {
+            Some(render_calendar_year(date.year, date.calendar))
+        }
+        DatePartForm::Year(form) => Some(render_year(date.year, form, ctx.locale(), omit_era)),
         DatePartForm::Month(form, strip_periods) => match form {
             MonthForm::Numeric => {
                 if date.month == 0 || date.month > 12 {
@@ -20,7 +20,7 @@ use citeproc_db::{CiteData, ClusterData, ClusterId, ClusterNumber, IntraNote};
 use citeproc_io::output::{markup::Markup, OutputFormat};
 use citeproc_io::{Cite, Name, Reference};
 use csl::GivenNameDisambiguationRule as GNDR;
-use csl::{Atom, Bibliography, Position, SortKey};
+use csl::{Atom, Bibliography, Locale, Position, SortKey};
 
 use indextree::NodeId;
 
@@ -55,6 +55,8 @@ pub trait IrDatabase:
     fn ir_gen2_matching_refs(&self, id: CiteId) -> Arc<Vec<Atom>>;
     fn year_suffixes(&self) -> Arc<FnvHashMap<Atom, u32>>;
     fn year_suffix_for(&self, ref_id: Atom) -> Option<u32>;
+    /// The document's cites, split into groups that can be disambiguated independently.
+    fn disamb_groups(&self) -> Arc<Vec<Vec<CiteId>>>;
     fn ir_fully_disambiguated(&self, key: CiteId) -> Arc<IrGen>;
     fn built_cluster(&self, key: ClusterId) -> Arc<MarkupOutput>;
 
@@ -63,6 +65,9 @@ pub trait IrDatabase:
 
     fn bib_item_gen0(&self, ref_id: Atom) -> Option<Arc<IrGen>>;
     fn bib_item(&self, ref_id: Atom) -> Arc<MarkupOutput>;
+    /// Every bibliography entry with subsequent-author-substitute applied. Each entry depends on
+    /// the one before it, so they are all done at once.
+    fn bib_items_substituted(&self) -> Arc<FnvHashMap<Atom, Arc<IrGen>>>;
     fn get_bibliography_map(&self) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>>;
 
     fn branch_runs(&self) -> Arc<FreeCondSets>;
@@ -84,6 +89,11 @@ pub trait IrDatabase:
     #[salsa::interned]
     fn disamb_name(&self, e: DisambNameData) -> DisambName;
 
+    /// Memoises [`DisambNameData::single_name_edge`]. Global name disambiguation renders the
+    /// same names in the same forms over and over.
+    #[salsa::invoke(crate::disamb::names::render_single_name_edge)]
+    fn single_name_edge(&self, data: DisambNameData, stack: Formatting) -> EdgeData;
+
     // Sorting
 
     // Includes intra-cluster sorting
@@ -112,12 +122,18 @@ pub trait IrDatabase:
     #[salsa::input]
     fn bibliography_no_sort(&self) -> bool;
 
+    /// The most names the add-names pass may add to any one names block before giving up and
+    /// leaving the cite ambiguous. `None` means no limit.
+    #[salsa::input]
+    fn add_names_limit(&self) -> Option<u16>;
+
     #[salsa::invoke(crate::sort::bib_number)]
     fn bib_number(&self, id: CiteId) -> Option<BibNumber>;
 }
 
 pub fn safe_default(db: &mut dyn IrDatabase) {
     db.set_bibliography_no_sort_with_durability(false, salsa::Durability::HIGH);
+    db.set_add_names_limit_with_durability(None, salsa::Durability::HIGH);
 }
 
 fn all_person_names(db: &dyn IrDatabase) -> Arc<Vec<DisambNameData>> {
@@ -299,6 +315,78 @@ fn year_suffixes(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, u32>> {
     Arc::new(suffixes)
 }
 
+/// Two cites are in the same group when their references have a family name in common, or when
+/// either cite could still be mistaken for the other's reference after adding given names (i.e.
+/// they might share year suffixes). Groups and the cites in them are in document order.
+///
+/// Every disambiguation query is pure, so this only decides which cites are worth computing
+/// together on one thread; it cannot change their output.
+fn disamb_groups(db: &dyn IrDatabase) -> Arc<Vec<Vec<CiteId>>> {
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    fn union(parent: &mut [usize], a: usize, b: usize) {
+        let (a, b) = (find(parent, a), find(parent, b));
+        if a != b {
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+
+    let participants = db.disamb_participants();
+    let mut parent: Vec<usize> = (0..participants.len()).collect();
+
+    let person_names = db.all_person_names();
+    let mut by_family: FnvHashMap<&str, usize> = FnvHashMap::default();
+    for dn in person_names.iter() {
+        let family = match dn.value.family.as_deref() {
+            Some(family) => family,
+            None => continue,
+        };
+        if let Some(ix) = participants.get_index_of(&dn.ref_id) {
+            let first = *by_family.entry(family).or_insert(ix);
+            union(&mut parent, first, ix);
+        }
+    }
+
+    let cite_ids = db.all_cite_ids();
+    let mut cite_refs = Vec::with_capacity(cite_ids.len());
+    for &cite_id in cite_ids.iter() {
+        let cite = cite_id.lookup(db);
+        let ix = participants.get_index_of(&cite.ref_id);
+        if let Some(ix) = ix {
+            for other in db.ir_gen2_matching_refs(cite_id).iter() {
+                if let Some(other) = participants.get_index_of(other) {
+                    union(&mut parent, ix, other);
+                }
+            }
+        }
+        cite_refs.push((cite_id, ix));
+    }
+
+    let mut group_of_root: FnvHashMap<usize, usize> = FnvHashMap::default();
+    let mut groups: Vec<Vec<CiteId>> = Vec::new();
+    for (cite_id, ix) in cite_refs {
+        match ix {
+            Some(ix) => {
+                let root = find(&mut parent, ix);
+                let next = groups.len();
+                let group = *group_of_root.entry(root).or_insert(next);
+                if group == next {
+                    groups.push(Vec::new());
+                }
+                groups[group].push(cite_id);
+            }
+            // Cites of missing references have nothing to disambiguate against.
+            None => groups.push(vec![cite_id]),
+        }
+    }
+    Arc::new(groups)
+}
+
 // Not cached
 fn ref_bib_number(bib_numbers: &FnvHashMap<Atom, BibNumber>, ref_id: &Atom) -> u32 {
     let ret = bib_numbers.get(ref_id).cloned();
@@ -320,6 +408,12 @@ pub struct IrGen {
     pub(crate) state: IrState,
     pub(crate) used_disambiguate_true: bool,
     pub(crate) disambiguation_finished: bool,
+    /// The disambiguation passes that were actually run on this tree, in order.
+    pub(crate) passes: Vec<DisambPass>,
+    /// The pass after which this cite first matched only its own reference. The given name pass
+    /// is never recorded here, because checking it would cost another ambiguity test per cite;
+    /// see `ir_gen2_matching_refs` for that.
+    pub(crate) resolved_by: Option<DisambPass>,
 }
 
 use std::fmt;
@@ -339,8 +433,23 @@ impl IrGen {
             state,
             used_disambiguate_true: false,
             disambiguation_finished,
+            passes: Vec::new(),
+            resolved_by: None,
         }
     }
+    /// The disambiguation passes applied to produce this tree, in the order they ran.
+    pub fn passes(&self) -> &[DisambPass] {
+        &self.passes
+    }
+    /// The pass that made this cite unambiguous. `None` if it was never ambiguous, or still is.
+    /// Never `AddGivenName`, which is not tested for until a report asks for it.
+    pub fn resolved_by(&self) -> Option<DisambPass> {
+        self.resolved_by
+    }
+    /// Whether this cite could still refer to more than one reference after disambiguation.
+    pub fn is_ambiguous(&self) -> bool {
+        !self.disambiguation_finished
+    }
     pub(crate) fn tree(&self) -> &IrTree {
         &self.tree
     }
@@ -375,7 +484,6 @@ fn ref_not_found(db: &dyn IrDatabase, ref_id: &Atom, log: bool) -> Arc<IrGen> {
 macro_rules! preamble {
     ($style:ident, $locale:ident, $cite:ident, $refr:ident, $ctx:ident, $db:expr, $id:expr, $pass:expr) => {{
         $style = $db.style();
-        $locale = $db.default_locale();
         // Avoid making bibliography ghosts all depend any positional / note num info
         let cite_stuff = match $db.lookup_cite($id) {
             CiteData::RealCite { cite, .. } => (cite, $db.cite_position($id)),
@@ -391,6 +499,7 @@ macro_rules! preamble {
             None => return ref_not_found($db, &$cite.ref_id, true),
             Some(r) => r,
         };
+        $locale = reference_locale($db, &$refr, $db.default_locale());
         let (names_delimiter, name_el) = $db.name_info_citation();
         $ctx = CiteContext {
             reference: &$refr,
@@ -1034,8 +1143,12 @@ impl IrGenCow {
         if ctx.style.citation.disambiguate_add_names {
             // Clone ir0; disambiguate by adding names
             let cloned = self.to_mut();
+            cloned.passes.push(DisambPass::AddNames);
             cloned.disambiguation_finished =
                 disambiguate_add_names(db, cloned.tree_mut(), ctx, false);
+            if cloned.disambiguation_finished {
+                cloned.resolved_by = Some(DisambPass::AddNames);
+            }
         }
     }
 
@@ -1045,6 +1158,8 @@ impl IrGenCow {
         }
         if ctx.style.citation.disambiguate_add_givenname {
             let cloned = self.to_mut();
+            let pass = DisambPass::AddGivenName(ctx.style.citation.givenname_disambiguation_rule);
+            cloned.passes.push(pass);
             let also_add_names = ctx.style.citation.disambiguate_add_names;
             disambiguate_add_givennames(db, cloned.tree_mut(), ctx, also_add_names);
         }
@@ -1059,7 +1174,11 @@ impl IrGenCow {
                 _ => return,
             };
             let cloned = self.to_mut();
+            cloned.passes.push(DisambPass::AddYearSuffix(year_suffix));
             ctx.disamb_pass = Some(DisambPass::AddYearSuffix(year_suffix));
+            // So that anything re-rendered by later passes (i.e. disambiguate="true" branches)
+            // can test and render the suffix.
+            ctx.year_suffix = Some(year_suffix);
             disambiguate_add_year_suffix(cloned.tree_mut(), &ctx, year_suffix);
             // if it's already unambiguous on names alone, then adding year suffixes is hardly
             // going to improve it. So avoid the cost.
@@ -1073,7 +1192,11 @@ impl IrGenCow {
         let gen = self.arc.deref();
         let unambiguous = is_unambiguous(db, gen.tree_ref(), &ctx.reference.id);
         if unambiguous != self.disambiguation_finished {
-            self.to_mut().disambiguation_finished = unambiguous;
+            let gen = self.to_mut();
+            gen.disambiguation_finished = unambiguous;
+            if unambiguous && gen.resolved_by.is_none() {
+                gen.resolved_by = ctx.disamb_pass;
+            }
         }
     }
 
@@ -1082,9 +1205,13 @@ impl IrGenCow {
             return;
         }
         let cloned = self.to_mut();
+        if !list_all_cond_disambs(cloned.tree_ref()).is_empty() {
+            cloned.passes.push(DisambPass::Conditionals);
+        }
         ctx.disamb_pass = Some(DisambPass::Conditionals);
         cloned.used_disambiguate_true = true;
         disambiguate_true(db, &mut cloned.tree, &mut cloned.state, &ctx);
+        self.update_is_ambiguous(db, ctx);
     }
 }
 
@@ -1119,6 +1246,19 @@ fn ir_fully_disambiguated(db: &dyn IrDatabase, id: CiteId) -> Arc<IrGen> {
     irgen.into_arc()
 }
 
+/// The locale to render a reference with. Styles with the `reference-locale` feature render a
+/// reference that has a `language` in that language; otherwise this is `default`.
+pub(crate) fn reference_locale(
+    db: &dyn IrDatabase,
+    refr: &Reference,
+    default: Arc<Locale>,
+) -> Arc<Locale> {
+    match refr.language.as_ref() {
+        Some(lang) if db.style().features.reference_locale => db.lang_locale(lang.clone()),
+        _ => default,
+    }
+}
+
 fn get_piq(db: &dyn IrDatabase) -> bool {
     // We pant PIQ to be global in a document, not change within a cluster because one cite
     // decided to use a different language. Use the default locale to get it.
@@ -1129,6 +1269,14 @@ fn get_piq(db: &dyn IrDatabase) -> bool {
         .unwrap_or(false)
 }
 
+/// The bibliography's PIQ, from the bibliography locale, which may differ from the citations'.
+fn get_bib_piq(db: &dyn IrDatabase) -> bool {
+    db.bibliography_locale()
+        .options_node
+        .punctuation_in_quote
+        .unwrap_or(false)
+}
+
 fn built_cluster(
     db: &dyn IrDatabase,
     cluster_id: ClusterId,
@@ -1139,6 +1287,47 @@ fn built_cluster(
     Arc::new(string)
 }
 
+/// The rendered cluster, along with a copy of the IR it was built from.
+pub fn built_cluster_with_ir(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+) -> (<Markup as OutputFormat>::Output, IrArena, NodeId) {
+    let fmt = db.get_formatter();
+    let (build, arena, root) = cluster::built_cluster_with_ir_before_output(db, cluster_id, &fmt);
+    (fmt.output(build, get_piq(db)), arena, root)
+}
+
+/// The rendered cluster, with everything disambiguation added to it marked. See
+/// `IrTreeRef::flatten_marking_disamb`. This is rendered straight from the cluster's IR, so it is
+/// only meant for debugging.
+pub fn built_cluster_disamb_debug(
+    db: &dyn IrDatabase,
+    cluster_id: ClusterId,
+) -> <Markup as OutputFormat>::Output {
+    let fmt = db.get_formatter();
+    let (_build, arena, root) = cluster::built_cluster_with_ir_before_output(db, cluster_id, &fmt);
+    let build = IrTreeRef::new(root, &arena)
+        .flatten_marking_disamb(&fmt, None)
+        .unwrap_or_else(|| fmt.plain(""));
+    fmt.output(build, get_piq(db))
+}
+
+/// A cite's edge stream after disambiguation: its output split wherever a year suffix, locator,
+/// citation number, etc. was rendered. This is the stream matched against every reference during
+/// disambiguation, for tools that need to see those boundaries.
+pub fn cite_edge_stream(db: &dyn IrDatabase, cite_id: CiteId) -> Vec<EdgeData> {
+    let gen = db.ir_fully_disambiguated(cite_id);
+    gen.tree_ref().to_edge_stream(&db.get_formatter())
+}
+
+/// A cite's IR after disambiguation, which serializes as a tree of its nodes.
+pub fn cite_ir_json(db: &dyn IrDatabase, cite_id: CiteId) -> crate::IrJson {
+    crate::IrJson {
+        gen: db.ir_fully_disambiguated(cite_id),
+        fmt: db.get_formatter(),
+    }
+}
+
 pub fn built_cluster_preview(
     db: &dyn IrDatabase,
     cluster_id: ClusterId,
@@ -1221,9 +1410,9 @@ pub fn with_cite_context<T>(
     f: impl FnOnce(CiteContext) -> T,
 ) -> Option<T> {
     let style = db.style();
-    let locale = db.default_locale();
     let cite = id.lookup(db);
     let refr = db.reference(cite.ref_id.clone())?;
+    let locale = reference_locale(db, &refr, db.default_locale());
     let (names_delimiter, name_el) = db.name_info_citation();
     let ctx = CiteContext {
         reference: &refr,
@@ -1263,7 +1452,6 @@ pub fn with_bib_context<T>(
 ) -> Option<T> {
     let style = db.style();
     let bib = style.bibliography.as_ref()?;
-    let locale = db.default_locale();
     let cite = Cite::basic(ref_id.clone());
     let null_ref = citeproc_io::Reference::empty("empty_ref".into(), csl::CslType::Article);
     let (refr, is_ref_missing) = if let Some(r) = refr {
@@ -1271,6 +1459,7 @@ pub fn with_bib_context<T>(
     } else {
         (&null_ref, true)
     };
+    let locale = reference_locale(db, refr, db.bibliography_locale());
     let (names_delimiter, name_el) = db.name_info_bibliography();
     let ctx = CiteContext {
         reference: &refr,
@@ -1327,7 +1516,7 @@ fn bib_item(db: &dyn IrDatabase, ref_id: Atom) -> Arc<MarkupOutput> {
     Arc::new(format_single_bib_item(
         gen0_arc.as_deref(),
         &fmt,
-        get_piq(db),
+        get_bib_piq(db),
     ))
 }
 
@@ -1341,7 +1530,48 @@ pub fn bib_item_preview(
 ) -> SmartString {
     // Pretend it's the first item in the bibliography
     let gen0_arc = bib_item_gen0_acontextual(db, ref_id, Some(refr), Some(1));
-    format_single_bib_item(gen0_arc.as_deref(), fmt, get_piq(db))
+    format_single_bib_item(gen0_arc.as_deref(), fmt, get_bib_piq(db))
+}
+
+/// For styles with `second-field-align`, renders the first field of a bibliography entry and the
+/// rest of it as two separate strings, as produced by `IR::split_first_field`, but without the
+/// left-margin/right-inline wrappers. Returns None if the style does not use second-field-align
+/// or the entry has no second field.
+pub fn bib_item_split(db: &dyn IrDatabase, ref_id: Atom) -> Option<(SmartString, SmartString)> {
+    let style = db.style();
+    style.bibliography.as_ref()?.second_field_align.as_ref()?;
+    let fmt = db.get_formatter();
+    let gen = db.bib_items_substituted().get(&ref_id)?.clone();
+    let mut tree = gen.tree.clone();
+
+    fn is_split(tree: &IrTree) -> bool {
+        tree.tree_ref()
+            .children()
+            .next()
+            .and_then(|first| match &first.get_node()?.get().0 {
+                IR::Seq(seq) => Some(seq.display == Some(DisplayMode::LeftMargin)),
+                _ => None,
+            })
+            .unwrap_or(false)
+    }
+
+    // second-field-align="flush" is already split in bib_item_gen0.
+    if !is_split(&tree) {
+        tree.root = IR::split_first_field(tree.root, &mut tree.arena)?;
+        transforms::fix_left_right_layout_affixes(tree.root, &mut tree.arena);
+    }
+    let halves: Vec<NodeId> = tree.root.children(&tree.arena).collect();
+    let piq = get_bib_piq(db);
+    let mut render_half = |node: NodeId| {
+        if let Some((IR::Seq(seq), _)) = tree.get_mut(node).map(|n| n.get_mut()) {
+            seq.display = None;
+        }
+        let flat = tree.tree_at_node(node).flatten(&fmt, None)?;
+        Some(fmt.output(flat, piq))
+    };
+    let left = render_half(*halves.get(0)?)?;
+    let rest = render_half(*halves.get(1)?).unwrap_or_default();
+    Some((left, rest))
 }
 
 fn bib_item_gen0_acontextual(
@@ -1373,6 +1603,7 @@ fn bib_item_gen0_acontextual(
             // versa"
             if let Some(suffix) = db.year_suffix_for(ref_id.clone()) {
                 ctx.disamb_pass = Some(DisambPass::AddYearSuffix(suffix));
+                ctx.year_suffix = Some(suffix);
                 disambiguate_add_year_suffix(&mut tree, &ctx, suffix);
             }
 
@@ -1456,53 +1687,82 @@ fn first_cite_used_disambiguate_true(db: &dyn IrDatabase, ref_id: Atom) -> bool
     })
 }
 
-fn get_bibliography_map(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>> {
+fn bib_items_substituted(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<IrGen>>> {
     let fmt = db.get_formatter();
     let style = db.style();
     let sorted_refs = db.sorted_refs();
     let mut m =
         FnvHashMap::with_capacity_and_hasher(sorted_refs.0.len(), fnv::FnvBuildHasher::default());
-    let mut prev: Option<(NodeId, Arc<IrGen>)> = None;
+    let mut prev: Option<(Vec<NodeId>, Arc<IrGen>)> = None;
+    let sas = style.bibliography.as_ref().and_then(|bib| {
+        bib.subsequent_author_substitute
+            .as_ref()
+            .map(|x| (x.as_ref(), bib.subsequent_author_substitute_rule))
+    });
+    let sas_all_names = style.features.subsequent_author_substitute_all_names;
     for key in sorted_refs.0.iter() {
         // TODO: put Nones in there so they can be updated
         if let Some(mut gen0) = db.bib_item_gen0(key.clone()) {
             // in a bibliography, we do the affixes etc inside Layout, so they're not here
-            let current = gen0.tree_ref().first_names_block();
-            let sas = style.bibliography.as_ref().and_then(|bib| {
-                bib.subsequent_author_substitute
-                    .as_ref()
-                    .map(|x| (x.as_ref(), bib.subsequent_author_substitute_rule))
-            });
-            if let (Some(prev_name_block), Some(current_name_block), Some((sas, sas_rule))) = (
-                prev.as_ref()
-                    .and_then(|(first_block, gen)| gen.tree.arena.get(*first_block)),
-                current,
-                sas,
-            ) {
-                let mutated = Arc::make_mut(&mut gen0);
-                let did = transforms::subsequent_author_substitute(
-                    &fmt,
-                    // In order to unwrap this here, you must only replace the NameIR node's
-                    // children, not the IR.
-                    prev_name_block.get().0.unwrap_name_ir(),
-                    current_name_block,
-                    &mut mutated.tree.arena,
-                    sas,
-                    sas_rule,
-                );
-                if did {
-                    mutated.tree_mut().recompute_group_vars();
+            let mut current = gen0.tree_ref().all_names_blocks();
+            if !sas_all_names {
+                current.truncate(1);
+            }
+            if let (Some((prev_blocks, prev_gen)), Some((sas, sas_rule))) = (prev.as_ref(), sas) {
+                let mut did_any = false;
+                // Later blocks are only substituted while every block before them was.
+                for (&prev_block, &current_block) in prev_blocks.iter().zip(current.iter()) {
+                    let prev_name_block = match prev_gen.tree.arena.get(prev_block) {
+                        Some(x) => x,
+                        None => break,
+                    };
+                    let mutated = Arc::make_mut(&mut gen0);
+                    let did = transforms::subsequent_author_substitute(
+                        &fmt,
+                        // In order to unwrap this here, you must only replace the NameIR node's
+                        // children, not the IR.
+                        prev_name_block.get().0.unwrap_name_ir(),
+                        current_block,
+                        &mut mutated.tree.arena,
+                        sas,
+                        sas_rule,
+                    );
+                    did_any |= did;
+                    if !did {
+                        break;
+                    }
+                }
+                if did_any {
+                    Arc::make_mut(&mut gen0).tree_mut().recompute_group_vars();
                 }
             }
-            let flat = gen0
+            m.insert(key.clone(), gen0.clone());
+            prev = if current.is_empty() {
+                None
+            } else {
+                Some((current, gen0))
+            };
+        }
+    }
+    Arc::new(m)
+}
+
+fn get_bibliography_map(db: &dyn IrDatabase) -> Arc<FnvHashMap<Atom, Arc<MarkupOutput>>> {
+    let fmt = db.get_formatter();
+    let sorted_refs = db.sorted_refs();
+    let substituted = db.bib_items_substituted();
+    let mut m =
+        FnvHashMap::with_capacity_and_hasher(sorted_refs.0.len(), fnv::FnvBuildHasher::default());
+    for key in sorted_refs.0.iter() {
+        if let Some(gen) = substituted.get(key) {
+            let flat = gen
                 .tree_ref()
                 .flatten(&fmt, None)
                 .unwrap_or_else(|| fmt.plain(""));
-            let string = fmt.output(flat, get_piq(db));
+            let string = fmt.output(flat, get_bib_piq(db));
             if !string.is_empty() {
                 m.insert(key.clone(), Arc::new(string));
             }
-            prev = current.map(|cur| (cur, gen0));
         }
     }
     Arc::new(m)
@@ -1514,8 +1774,7 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
 
     let mut map = FnvHashMap::default();
 
-    let style = db.style();
-    let near_note_distance = style.citation.near_note_distance;
+    let near_note_distance = db.near_note_distance();
 
     // Backref table for FRNN
     // No entries for first ref == an in-text reference, only first time it appeared in a
@@ -1579,7 +1838,13 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
                             ClusterNumber::Note(intra) => Some(intra.note_number()),
                             _ => None,
                         };
-                        let cites_all_same = if prev_in_group && !in_text {
+                        let same_note = match (prev_cluster.number, cluster.number) {
+                            (ClusterNumber::Note(a), ClusterNumber::Note(b)) => {
+                                a.note_number() == b.note_number()
+                            }
+                            _ => false,
+                        };
+                        let cites_all_same = if prev_in_group && !in_text && !same_note {
                             // { id: 1, note: 4, cites: [A] },
                             // { id: 2, note: 4, cites: [B] },
                             // { id: 3: note: 5, cites: [B] } => subsequent
@@ -1593,6 +1858,13 @@ fn cite_positions(db: &dyn IrDatabase) -> Arc<FnvHashMap<CiteId, (Position, Opti
                                         .all(|cite_id| cite_id.lookup(db).ref_id == cite.ref_id)
                                 })
                         } else {
+                            // Within a note, only the preceding cluster counts:
+                            // { id: 1, note: 4, cites: [A] },
+                            // { id: 2, note: 4, cites: [B] },
+                            // { id: 3, note: 4, cites: [B] } => ibid
+                            // but it has to cite nothing else:
+                            // { id: 1, note: 4, cites: [A, B] },
+                            // { id: 2, note: 4, cites: [B] } => subsequent
                             prev_cluster
                                 .cites
                                 .iter()
@@ -13,6 +13,9 @@ use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::fmt::Debug;
 
+/// One piece of a cite's output as seen by disambiguation. Rendered text is `Output`; the other
+/// variants mark where a value that varies between cites of the same reference was rendered, so
+/// matching a cite against a reference does not depend on it.
 // XXX(pandoc): maybe force this to be a string and coerce pandoc output into a string
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum EdgeData<O = <Markup as OutputFormat>::Output> {
@@ -477,6 +477,22 @@ impl FreeCondSets {
         all.0.extend(accumulator.0.drain());
         all.at_least_1()
     }
+    /// Combines sets where exactly one of the alternatives applies, like the elements of a
+    /// `<substitute>`. Unlike `cross_product`, this is a union; a set that is a strict subset of
+    /// another is dropped, since the larger one already covers every reference it describes.
+    pub fn union_of_alternatives(alternatives: impl Iterator<Item = Self>) -> Self {
+        let mut all = FreeCondSets::empty();
+        for alt in alternatives {
+            all.0.extend(alt.0);
+        }
+        let pruned = all
+            .0
+            .iter()
+            .filter(|&&a| !all.0.iter().any(|&b| a != b && b.contains(a)))
+            .cloned()
+            .collect();
+        FreeCondSets(pruned).at_least_1()
+    }
     pub fn insert_validated(&mut self, a: FreeCond) {
         if !a.is_incompatible() {
             self.0.insert(a);
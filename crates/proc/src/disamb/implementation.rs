@@ -18,7 +18,7 @@ impl Disambiguation<Markup> for Style {
         state: &mut IrState,
         stack: Formatting,
     ) -> (RefIR, GroupVars) {
-        let els = &self.citation.layout.elements;
+        let els = &self.citation.layout_for(ctx.cite_lang()).elements;
         ref_sequence(db, state, ctx, els, false, Some(stack), None)
     }
 }
@@ -44,6 +44,7 @@ impl Disambiguation<Markup> for Group {
             Some(&|| RefIrSeq {
                 delimiter: self.delimiter.clone(),
                 affixes: self.affixes.clone(),
+                text_case: self.text_case,
                 ..Default::default()
             }),
         );
@@ -158,9 +159,34 @@ impl Disambiguation<Markup> for Element {
                         }
                         _ => {}
                     }
+                    if let StandardVariable::Ordinary(v) = var {
+                        if let Some(values) = ctx.get_ordinary_multi(v, form) {
+                            let contents: Vec<RefIR> = state
+                                .maybe_suppress(v, |_| {
+                                    Some(renderer.text_variable_multi(text, v, values))
+                                })
+                                .unwrap_or_default()
+                                .into_iter()
+                                .map(|x| fmt.output_in_context(x, stack, None))
+                                .map(|out| RefIR::Edge(Some(EdgeData::Output(out))))
+                                .collect();
+                            if contents.is_empty() {
+                                return (RefIR::Edge(None), GroupVars::Missing);
+                            }
+                            let seq = RefIrSeq {
+                                contents,
+                                affixes: text.affixes.clone(),
+                                formatting: text.formatting,
+                                quotes: renderer.quotes_if(text.quotes),
+                                should_inherit_delim: true,
+                                ..Default::default()
+                            };
+                            return (RefIR::Seq(seq), GroupVars::Important);
+                        }
+                    }
                     let content = match var {
                         StandardVariable::Ordinary(v) => state.maybe_suppress(v, |_| {
-                            ctx.get_ordinary(v, form)
+                            ctx.get_ordinary_variant(v, form, text.language.as_ref())
                                 .map(|val| renderer.text_variable(text, var, &val))
                         }),
                         StandardVariable::Number(v) => state.maybe_suppress_num(v, |_| {
@@ -166,15 +166,24 @@ impl<'a> StyleWalker for FreeCondWalker<'a> {
     }
 
     fn names(&mut self, names: &Names) -> Self::Output {
-        let mut base = if let Some(subst) = &names.substitute {
-            // TODO: drill down into the substitute logic here
-            self.fold(&subst.0, WalkerFoldType::Substitute)
-        } else {
-            FreeCondSets::mult_identity()
-        };
-        // Position may be involved for NASO and primary disambiguation
-        let cond = Cond::Position(Position::First);
-        base.scalar_multiply_cond(cond, true);
+        // Names render differently by position: ibid cites are never expanded by cite-time
+        // given name disambiguation (see `SingleNameDisambMethod::from_rule`), so the names
+        // are evaluated as a non-ibid (first) cite.
+        let position = || Cond::Position(Position::First);
+        let mut base = FreeCondSets::mult_identity();
+        base.scalar_multiply_cond(position(), true);
+        if let Some(subst) = &names.substitute {
+            // A substitute element only renders when the names variables and every earlier
+            // substitute element were empty, so each is an alternative to the names themselves
+            // rather than something rendered alongside them.
+            let mut alternatives = vec![base];
+            for el in subst.0.iter() {
+                let mut alt = self.element(el);
+                alt.scalar_multiply_cond(position(), true);
+                alternatives.push(alt);
+            }
+            base = FreeCondSets::union_of_alternatives(alternatives.into_iter());
+        }
         base
     }
 
@@ -273,7 +282,7 @@ pub fn create_ref_ir<O: OutputFormat>(
     refr: &Reference,
 ) -> Vec<(FreeCond, RefIR)> {
     let style = db.style();
-    let locale = db.default_locale();
+    let locale = crate::db::reference_locale(db, refr, db.default_locale());
     let ysh_explicit_edge = EdgeData::YearSuffixExplicit;
     let ysh_plain_edge = EdgeData::YearSuffixPlain;
     let ysh_edge = EdgeData::YearSuffix;
@@ -44,6 +44,7 @@ impl Disambiguation<Markup> for Names {
             fmt,
             demote_non_dropping_particle: style.demote_non_dropping_particle,
             initialize_with_hyphen: style.initialize_with_hyphen,
+            is_english: crate::cite_context::is_english(style, ctx.reference.language.as_ref()),
         };
 
         let mut seq = RefIrSeq {
@@ -276,27 +277,42 @@ impl DisambNameData {
     /// in a formatting context `stack`.
     /// This is used directly for *global name disambiguation*, and for ratcheting one name
     /// forward in NameIR expansion.
+    ///
+    /// Cached by the `single_name_edge` query.
     pub(crate) fn single_name_edge(&self, db: &dyn IrDatabase, stack: Formatting) -> EdgeData {
-        let fmt = &db.get_formatter();
-        let style = db.style();
-        let builder = OneNameVar {
-            fmt,
-            name_el: &self.el,
-            bump_name_count: 0,
-            demote_non_dropping_particle: style.demote_non_dropping_particle,
-            initialize_with_hyphen: style.initialize_with_hyphen,
-        };
-        let built = builder.render_person_name(&self.value, !self.primary);
-        let o = fmt.output_in_context(built, stack, None);
-        EdgeData::Output(o)
+        db.single_name_edge(self.clone(), stack)
     }
 
-    fn disamb_iter(&self, rule: GNDR) -> SingleNameDisambIter {
-        let method = SingleNameDisambMethod::from_rule(rule, self.primary);
+    /// `position` is that of the cite being disambiguated, or `None` when the name is being
+    /// disambiguated globally or for a RefIR, which have to cover every position.
+    fn disamb_iter(&self, rule: GNDR, position: Option<Position>) -> SingleNameDisambIter {
+        let method = SingleNameDisambMethod::from_rule(rule, self.primary, position);
         SingleNameDisambIter::new(method, &self.el)
     }
 }
 
+pub(crate) fn render_single_name_edge(
+    db: &dyn IrDatabase,
+    data: DisambNameData,
+    stack: Formatting,
+) -> EdgeData {
+    let fmt = &db.get_formatter();
+    let style = db.style();
+    let refr = db.reference(data.ref_id.clone());
+    let item_lang = refr.as_ref().and_then(|refr| refr.language.as_ref());
+    let builder = OneNameVar {
+        fmt,
+        name_el: &data.el,
+        bump_name_count: 0,
+        demote_non_dropping_particle: style.demote_non_dropping_particle,
+        initialize_with_hyphen: style.initialize_with_hyphen,
+        is_english: crate::cite_context::is_english(&style, item_lang),
+    };
+    let built = builder.render_person_name(&data.value, !data.primary);
+    let o = fmt.output_in_context(built, stack, None);
+    EdgeData::Output(o)
+}
+
 /// The GNDR variants are poorly worded. "-with-initials" doesn't *add*
 /// steps, it removes steps / limits the expansion. This is a bit clearer to work with, and mixes
 /// in the information about whether a name is primary or not.
@@ -309,7 +325,14 @@ enum SingleNameDisambMethod {
 
 impl SingleNameDisambMethod {
     /// `is_primary` refers to whether this is the first name to be rendered in a Names element.
-    fn from_rule(rule: GNDR, is_primary: bool) -> Self {
+    ///
+    /// An ibid cite points at the reference cited immediately before it, which has already been
+    /// disambiguated there, so its names are never expanded at cite time. (Names expanded by
+    /// global name disambiguation stay expanded, so they still render consistently.)
+    fn from_rule(rule: GNDR, is_primary: bool, position: Option<Position>) -> Self {
+        if position.map_or(false, |pos| pos.matches(Position::Ibid)) {
+            return SingleNameDisambMethod::None;
+        }
         match (rule, is_primary) {
             (GNDR::ByCite, _) | (GNDR::AllNames, _) => {
                 SingleNameDisambMethod::AddInitialsThenGivenName
@@ -416,7 +439,7 @@ pub enum NameDisambPass {
 
 #[cfg(test)]
 fn test(name: &NameEl, rule: GNDR, primary: bool) -> Vec<NameDisambPass> {
-    let method = SingleNameDisambMethod::from_rule(rule, primary);
+    let method = SingleNameDisambMethod::from_rule(rule, primary, None);
     let iter = SingleNameDisambIter::new(method, name);
     let passes: Vec<_> = iter.collect();
     passes
@@ -453,6 +476,10 @@ fn test_name_disamb_iter() {
         test(&name, GNDR::AllNamesWithInitials, true),
         vec![NameDisambPass::WithFormLong]
     );
+    let method = SingleNameDisambMethod::from_rule(GNDR::ByCite, true, Some(Position::Ibid));
+    assert_eq!(method, SingleNameDisambMethod::None);
+    let method = SingleNameDisambMethod::from_rule(GNDR::ByCite, true, Some(Position::Subsequent));
+    assert_eq!(method, SingleNameDisambMethod::AddInitialsThenGivenName);
 }
 
 /// Original + expansions
@@ -474,7 +501,7 @@ fn add_expanded_name_to_graph(
     nfa.graph.add_edge(last, next_spot, NfaEdge::Epsilon);
 
     // then all the expansions the name can have
-    for pass in dn.disamb_iter(rule) {
+    for pass in dn.disamb_iter(rule, None) {
         dn.apply_upto_pass(pass);
         let first = nfa.graph.add_node(());
         nfa.start.insert(first);
@@ -543,7 +570,7 @@ impl NameVariantMatcher {
         let mut edges = SmallVec::new();
         let edge = data.single_name_edge(db, Formatting::default());
         edges.push(edge);
-        for pass in data.disamb_iter(rule) {
+        for pass in data.disamb_iter(rule, None) {
             data.apply_upto_pass(pass);
             let edge = data.single_name_edge(db, Formatting::default());
             edges.push(edge);
@@ -553,6 +580,42 @@ impl NameVariantMatcher {
             family_match_key: Some((data.ref_id.clone(), data.var, data.primary)),
         }
     }
+
+    /// Literal names have no disambiguated forms, so they only ever match the one edge.
+    pub fn from_literal(edge: EdgeData) -> Self {
+        let mut edges = SmallVec::new();
+        edges.push(edge);
+        NameVariantMatcher {
+            edges,
+            family_match_key: None,
+        }
+    }
+}
+
+/// Every distinct literal (institutional) name in the references being disambiguated, rendered
+/// the way a names block renders it.
+fn all_literal_name_edges(db: &dyn IrDatabase) -> Vec<EdgeData> {
+    let fmt = &db.get_formatter();
+    let name_configurations = db.name_configurations();
+    let refs = db.disamb_participants();
+    let mut edges = Vec::new();
+    for ref_id in refs.iter() {
+        if let Some(refr) = db.reference(ref_id.clone()) {
+            for (var, _el) in name_configurations.iter() {
+                for name in refr.name.get(var).into_iter().flatten() {
+                    if let Name::Literal { literal, .. } = name {
+                        let b = fmt.text_node(literal.clone(), None);
+                        let edge =
+                            EdgeData::Output(fmt.output_in_context(b, Formatting::default(), None));
+                        if !edges.contains(&edge) {
+                            edges.push(edge);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    edges
 }
 
 /// Performs 'global name disambiguation'
@@ -576,6 +639,11 @@ pub fn disambiguated_person_names(
     for dn in dns.iter().cloned() {
         matchers.push(NameVariantMatcher::from_disamb_name(db, dn));
     }
+    // A person's name can clash with an organisation's, even though only the person's can be
+    // expanded.
+    for edge in all_literal_name_edges(db) {
+        matchers.push(NameVariantMatcher::from_literal(edge));
+    }
     let is_ambiguous = |edge: &EdgeData, same: Option<&MatchKey>| -> bool {
         let mut n = 0;
         for m in &matchers {
@@ -594,7 +662,7 @@ pub fn disambiguated_person_names(
         let dn_id = db.disamb_name(orig.clone());
         let mut dn = orig.clone();
         let mut edge = dn.single_name_edge(db, Formatting::default());
-        let mut iter = dn.disamb_iter(rule);
+        let mut iter = dn.disamb_iter(rule, None);
         let key = dn.family_match_key();
         let mut max_pass = NameDisambPass::Initial;
         while is_ambiguous(&edge, key.as_ref()) {
@@ -653,9 +721,14 @@ pub struct PersonDisambNameRatchet {
 }
 
 impl PersonDisambNameRatchet {
-    pub fn new(style: &Style, id: DisambName, data: DisambNameData) -> Self {
+    pub fn new(
+        style: &Style,
+        id: DisambName,
+        data: DisambNameData,
+        position: Option<Position>,
+    ) -> Self {
         let rule = style.citation.givenname_disambiguation_rule;
-        let iter = data.disamb_iter(rule);
+        let iter = data.disamb_iter(rule, position);
         // debug!("{} ratchet started with state {:?}", &data.ref_id, iter);
         PersonDisambNameRatchet { id, iter, data }
     }
@@ -691,10 +764,11 @@ pub struct NameIR<O: OutputFormat> {
     pub disamb_names: Vec<DisambNameRatchet<O::Build>>,
     pub built_label: Option<O::Build>,
 
-    // These three avoid having to pass in style & locale every time you want to recompute the IR
+    // These avoid having to pass in style & locale every time you want to recompute the IR
     // or make name tokens.
     pub demote_non_dropping_particle: DemoteNonDroppingParticle,
     pub initialize_with_hyphen: bool,
+    pub is_english: bool,
     pub etal_term: Option<(SmartString, Option<Formatting>)>,
     pub and_term: Option<SmartString>,
 }
@@ -726,6 +800,7 @@ where
             achieved_at: (std::u16::MAX, NameCounter::default()),
             demote_non_dropping_particle: style.demote_non_dropping_particle,
             initialize_with_hyphen: style.initialize_with_hyphen,
+            is_english: gen_ctx.is_english(),
             etal_term,
             and_term,
             built_label,
@@ -757,12 +832,17 @@ where
         )
     }
 
-    // returns false if couldn't add any more names
+    // returns None if couldn't add any more names, or the add_names_limit has been reached
     pub fn add_name(
         &mut self,
-        _db: &dyn IrDatabase,
+        db: &dyn IrDatabase,
         ctx: &CiteContext<'_, O>,
     ) -> Option<Vec<O::Build>> {
+        if let Some(limit) = db.add_names_limit() {
+            if self.name_counter.bump >= limit {
+                return None;
+            }
+        }
         self.name_counter.bump += 1;
         self.intermediate_custom(
             &ctx.format,
@@ -207,6 +207,9 @@ where
     fn features(&self) -> &Features {
         &self.style.features
     }
+    fn has_flag(&self, flag: &str) -> bool {
+        self.reference.flags.contains(flag)
+    }
 }
 
 struct DisambCounter<'a, O: OutputFormat> {
@@ -257,7 +260,7 @@ mod test {
     use super::*;
     use crate::disamb::FreeCond;
 
-    use crate::test::with_test_citation;
+    use crate::test::{with_test_citation, with_test_style};
     use citeproc_db::LocaleFetcher;
     use csl::Atom;
     use csl::Lang;
@@ -337,5 +340,19 @@ mod test {
             ),
             0,
         );
+        // The branch is only in a locale layout
+        assert_eq!(
+            with_test_style(
+                r#"<style class="note" version="1.0.1">
+                    <features><feature name="locale-layouts" /></features>
+                    <citation>
+                        <layout locale="zh"><choose><if disambiguate="true" /></choose></layout>
+                        <layout></layout>
+                    </citation>
+                </style>"#,
+                count_plain,
+            ),
+            1,
+        );
     }
 }
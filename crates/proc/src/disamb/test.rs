@@ -79,6 +79,51 @@ fn test_locator_macro() {
     assert_eq!(fcs, correct);
 }
 
+#[test]
+fn test_substitute_alternatives() {
+    let mut db = MockProcessor::new();
+    db.set_style_text(style_text_layout!(
+        r#"<names variable="author">
+      <substitute>
+        <names variable="editor" />
+        <choose>
+          <if locator="page">
+            <text variable="locator"/>
+          </if>
+          <else-if variable="locator">
+            <text variable="locator" />
+          </else-if>
+        </choose>
+        <choose>
+          <if disambiguate="true">
+            <text variable="title"/>
+          </if>
+        </choose>
+      </substitute>
+    </names>"#
+    ));
+    let fcs = get_free_conds(&db);
+    // Multiplying the three substitutes together would give 2 * 3 * 2 sets, all with FIRST,
+    // plus FIRST_FALSE. Only one of them renders, so they are added instead, and FIRST on its
+    // own is covered by the more specific sets.
+    let mut correct = FreeCondSets::empty();
+    correct.0.insert(FreeCond::FIRST_FALSE);
+    correct
+        .0
+        .insert(FreeCond::FIRST | FreeCond::LOCATOR | FreeCond::LT_PAGE);
+    correct
+        .0
+        .insert(FreeCond::FIRST | FreeCond::LOCATOR | FreeCond::LT_PAGE_FALSE);
+    correct
+        .0
+        .insert(FreeCond::FIRST | FreeCond::LOCATOR_FALSE | FreeCond::LT_PAGE_FALSE);
+    correct.0.insert(FreeCond::FIRST | FreeCond::DISAMBIGUATE);
+    correct
+        .0
+        .insert(FreeCond::FIRST | FreeCond::DISAMBIGUATE_FALSE);
+    assert_eq!(fcs, correct);
+}
+
 use crate::disamb::{create_dfa, create_ref_ir};
 
 #[test]
@@ -160,3 +205,88 @@ fn test() {
 //     let italic = EdgeData("<i>italic</i>".to_string());
 //     assert!(dfa.accepts(&[value, italic]));
 // }
+
+#[test]
+fn edge_stream_for_other_formats() {
+    use crate::sort::SortStringFormat;
+    let fmt = SortStringFormat;
+    let mut arena = IrArena::<SortStringFormat>::new();
+    let seq = arena.new_node((
+        IR::Seq(IrSeq {
+            delimiter: Some(", ".into()),
+            affixes: Some(Affixes {
+                prefix: "(".into(),
+                suffix: ")".into(),
+            }),
+            ..Default::default()
+        }),
+        GroupVars::Important,
+    ));
+    for text in &["Smith", "2000"] {
+        let child = arena.new_node((
+            IR::Rendered(Some(CiteEdgeData::Output(fmt.plain(text)))),
+            GroupVars::Important,
+        ));
+        seq.append(child, &mut arena);
+    }
+    let edges = IrTreeRef::new(seq, &arena).to_edge_stream(&fmt);
+    let output = |s: &str| EdgeData::Output(s.into());
+    assert_eq!(
+        edges,
+        vec![
+            output("("),
+            output("Smith"),
+            output(", "),
+            output("2000"),
+            output(")")
+        ]
+    );
+}
+
+#[test]
+fn ref_ir_substitute() {
+    use citeproc_io::{Name, PersonName};
+    use csl::NameVariable;
+    let db = &mut MockProcessor::new();
+    db.set_style_text(style_text_layout!(
+        r#"<names variable="author">
+          <substitute>
+            <names variable="editor" />
+          </substitute>
+        </names>"#
+    ));
+    let mut refr = Reference::empty("ref_id".into(), CslType::Book);
+    refr.name.insert(
+        NameVariable::Editor,
+        vec![Name::Person(PersonName {
+            family: Some("Smith".into()),
+            given: Some("John".into()),
+            ..Default::default()
+        })],
+    );
+    let no_names = Reference::empty("no_names".into(), CslType::Book);
+
+    // The editor is substituted into the ref IR, rather than the names block being empty
+    let runs = create_ref_ir::<Markup>(db, &refr);
+    assert!(runs.iter().all(|(_fc, ir)| !ir.is_empty()));
+    let dfa = create_dfa::<Markup>(db, &refr);
+    let empty_dfa = create_dfa::<Markup>(db, &no_names);
+
+    db.insert_references(vec![refr, no_names]);
+    use citeproc_db::ClusterId;
+    let mut interner = string_interner::StringInterner::<ClusterId>::new();
+    let id = interner.get_or_intern("1");
+    db.init_clusters(vec![(
+        id,
+        ClusterNumber::Note(IntraNote::Single(1)),
+        vec![Cite::basic("ref_id")],
+    )]);
+    let cite_id = db.cluster_cites(id)[0];
+    let cite_edges = db
+        .ir_gen0(cite_id)
+        .tree_ref()
+        .to_edge_stream(&db.get_formatter());
+    assert!(!cite_edges.is_empty());
+    assert!(dfa.accepts_data(&cite_edges));
+    assert!(!empty_dfa.accepts_data(&cite_edges));
+}
@@ -14,7 +14,7 @@ where
         ctx: &CiteContext<'c, O, I>,
         arena: &mut IrArena<O>,
     ) -> NodeId {
-        let layout = &self.layout;
+        let layout = self.layout_for(ctx.cite_lang());
         sequence(
             db,
             state,
@@ -180,14 +180,29 @@ where
                                     arena.new_node((IR::Rendered(None), GroupVars::Missing))
                                 });
                         }
+                        if let StandardVariable::Ordinary(v) = var {
+                            if let Some(values) = ctx.get_ordinary_multi(v, form) {
+                                let builds = state.maybe_suppress(v, |_| {
+                                    Some(renderer.text_variable_multi(text, v, values))
+                                });
+                                return multi_value_seq(text, var, &renderer, builds, arena);
+                            }
+                        }
                         let content = match var {
                             StandardVariable::Ordinary(v) => state.maybe_suppress(v, |_| {
-                                ctx.get_ordinary(v, form)
+                                ctx.get_ordinary_variant(v, form, text.language.as_ref())
                                     .map(|val| renderer.text_variable(text, var, &val))
                             }),
                             StandardVariable::Number(v) => state.maybe_suppress_num(v, |_| {
-                                ctx.get_number(v)
-                                    .map(|val| renderer.text_number_variable(text, v, &val))
+                                ctx.get_number(v).map(|val| {
+                                    let b = renderer.text_number_variable(text, v, &val);
+                                    match v {
+                                        NumberVariable::Locator => {
+                                            renderer.append_locators(b, ctx.extra_locators())
+                                        }
+                                        _ => b,
+                                    }
+                                })
                             }),
                         };
                         let content = content.map(CiteEdgeData::from_standard_variable(var, false));
@@ -220,6 +235,12 @@ where
                 let content = state.maybe_suppress_num(var, |_| {
                     ctx.get_number(var)
                         .map(|val| renderer.number(number, &val))
+                        .map(|b| match var {
+                            NumberVariable::Locator => {
+                                renderer.append_locators(b, ctx.extra_locators())
+                            }
+                            _ => b,
+                        })
                         .map(CiteEdgeData::Output)
                 });
                 let gv = GroupVars::rendered_if(content.is_some());
@@ -240,6 +261,7 @@ where
                     formatting: g.formatting,
                     affixes: g.affixes.clone(),
                     display: g.display,
+                    text_case: g.text_case,
                     ..Default::default()
                 }),
             ),
@@ -254,6 +276,36 @@ where
     }
 }
 
+/// Each value of a repeatable variable goes in a sequence that inherits the delimiter of the
+/// enclosing group.
+fn multi_value_seq<O: OutputFormat, I: OutputFormat>(
+    text: &TextElement,
+    var: StandardVariable,
+    renderer: &Renderer<O, I>,
+    builds: Option<Vec<O::Build>>,
+    arena: &mut IrArena<O>,
+) -> NodeId {
+    let builds = match builds {
+        Some(builds) if !builds.is_empty() => builds,
+        _ => return arena.new_node((IR::Rendered(None), GroupVars::Missing)),
+    };
+    let seq = IrSeq {
+        formatting: text.formatting,
+        affixes: text.affixes.clone(),
+        display: text.display,
+        quotes: renderer.quotes_if(text.quotes),
+        should_inherit_delim: true,
+        ..Default::default()
+    };
+    let seq_node = arena.new_node((IR::Seq(seq), GroupVars::Important));
+    for build in builds {
+        let content = CiteEdgeData::from_standard_variable(var, false)(build);
+        let node = arena.new_node((IR::Rendered(Some(content)), GroupVars::Important));
+        seq_node.append(node, arena);
+    }
+    seq_node
+}
+
 impl YearSuffixHook {
     pub(crate) fn render<'c, O: OutputFormat, I: OutputFormat>(
         &self,
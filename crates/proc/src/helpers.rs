@@ -136,6 +136,9 @@ pub fn plain_text_element(v: Variable) -> TextElement {
         strip_periods: false,
         text_case: TextCase::None,
         display: None,
+        max_length: None,
+        page_range_format: None,
+        language: None,
     }
 }
 
@@ -12,8 +12,11 @@ use csl::{NumberVariable, StandardVariable, Variable};
 
 use std::sync::Arc;
 
+mod serialize;
 pub mod transforms;
 
+pub use serialize::IrJson;
+
 pub type IrSum<O> = (IR<O>, GroupVars);
 
 // Intermediate Representation
@@ -246,6 +249,10 @@ where
             (IR::YearSuffix(a), IR::YearSuffix(b)) if a == b => {}
             (IR::ConditionalDisamb(a), IR::ConditionalDisamb(b)) if a == b => {}
             (IR::Name(a), IR::Name(b)) if a == b => {}
+            // Count equality, not structural: different names can produce the same count, and
+            // they render identically. The rendered count is the only child, so comparing
+            // children compares counts; name_irs are left alone.
+            (IR::NameCounter(a), IR::NameCounter(b)) if a.group_vars == b.group_vars => {}
             (IR::Substitute, IR::Substitute) => {}
             _ => return false,
         }
@@ -308,12 +315,14 @@ impl<O: OutputFormat> CiteEdgeData<O> {
     }
 }
 
-impl IR<Markup> {
+/// Edge streams only need each format to produce strings, so any `OutputFormat<Output =
+/// SmartString>` can be compared against the RefIR automata, not just HTML.
+impl<O: OutputFormat<Output = SmartString>> IR<O> {
     fn append_edges(
         node: NodeId,
-        arena: &IrArena<Markup>,
+        arena: &IrArena<O>,
         edges: &mut Vec<EdgeData>,
-        fmt: &Markup,
+        fmt: &O,
         formatting: Formatting,
         inherit_delim: Option<&str>,
     ) {
@@ -349,9 +358,9 @@ impl IR<Markup> {
 
     fn append_child_edges(
         node: NodeId,
-        arena: &IrArena<Markup>,
+        arena: &IrArena<O>,
         edges: &mut Vec<EdgeData>,
-        fmt: &Markup,
+        fmt: &O,
         formatting: Formatting,
         inherit_delim: Option<&str>,
     ) {
@@ -404,26 +413,63 @@ impl<'a, O: OutputFormat<Output = SmartString>> IrTreeRef<'a, O> {
     /// Assumes any group vars have been resolved, so every item touched by flatten should in fact
     /// be rendered
     pub(crate) fn flatten(&self, fmt: &O, override_delim: Option<&str>) -> Option<O::Build> {
+        self.flatten_inner(fmt, override_delim, false)
+    }
+
+    /// Like `flatten`, but wraps whatever disambiguation added in a labelled marker: `{ys:a}` for
+    /// a year suffix, `{disamb:...}` for a re-rendered `disambiguate="true"` branch and
+    /// `{add-names:...}` for a names block that had names added. Given names added to a name are
+    /// not marked.
+    pub(crate) fn flatten_marking_disamb(
+        &self,
+        fmt: &O,
+        override_delim: Option<&str>,
+    ) -> Option<O::Build> {
+        self.flatten_inner(fmt, override_delim, true)
+    }
+
+    fn flatten_inner(
+        &self,
+        fmt: &O,
+        override_delim: Option<&str>,
+        mark_disamb: bool,
+    ) -> Option<O::Build> {
         // must clone
-        match self.arena.get(self.node)?.get().0 {
+        let ir = &self.arena.get(self.node)?.get().0;
+        let built = match *ir {
             IR::Rendered(None) => None,
             IR::Rendered(Some(ref x)) => Some(x.inner()),
-            IR::ConditionalDisamb(_) => self.flatten_children(fmt, override_delim),
+            IR::ConditionalDisamb(_) => self.flatten_children(fmt, override_delim, mark_disamb),
             IR::YearSuffix(_) | IR::NameCounter(_) | IR::Name(_) | IR::Substitute => {
-                self.flatten_children(fmt, None)
+                self.flatten_children(fmt, None, mark_disamb)
             }
-            IR::Seq(ref seq) => seq.flatten_seq(*self, fmt, override_delim),
+            IR::Seq(ref seq) => seq.flatten_seq(*self, fmt, override_delim, mark_disamb),
+        };
+        if !mark_disamb {
+            return built;
         }
+        let label = match ir {
+            IR::YearSuffix(_) => "ys",
+            IR::ConditionalDisamb(cond) if cond.done => "disamb",
+            IR::Name(nir) if nir.name_counter.bump > 0 => "add-names",
+            _ => return built,
+        };
+        built.map(|b| {
+            let open = fmt.plain(&format!("{{{}:", label));
+            fmt.group(vec![open, b, fmt.plain("}")], "", None)
+        })
     }
-    pub(crate) fn flatten_children(
+
+    fn flatten_children(
         &self,
         fmt: &O,
         override_delim: Option<&str>,
+        mark_disamb: bool,
     ) -> Option<O::Build> {
         let mut group = Vec::new();
         for child in self
             .children()
-            .filter_map(|child| child.flatten(fmt, override_delim))
+            .filter_map(|child| child.flatten_inner(fmt, override_delim, mark_disamb))
         {
             group.push(child)
         }
@@ -434,8 +480,8 @@ impl<'a, O: OutputFormat<Output = SmartString>> IrTreeRef<'a, O> {
     }
 }
 
-impl<'a> IrTreeRef<'a, Markup> {
-    pub fn to_edge_stream(&self, fmt: &Markup) -> Vec<EdgeData> {
+impl<'a, O: OutputFormat<Output = SmartString>> IrTreeRef<'a, O> {
+    pub fn to_edge_stream(&self, fmt: &O) -> Vec<EdgeData> {
         let mut edges = Vec::new();
         IR::append_edges(
             self.node,
@@ -456,6 +502,7 @@ impl IrSeq {
         tree: IrTreeRef<O>,
         fmt: &O,
         override_delim: Option<&str>,
+        mark_disamb: bool,
     ) -> Option<O::Build> {
         // Do this where it won't require mut access
         // self.recompute_group_vars();
@@ -477,7 +524,7 @@ impl IrSeq {
         } = *self;
         let xs: Vec<_> = tree
             .children()
-            .filter_map(|child| child.flatten(fmt, delimiter.as_opt_str()))
+            .filter_map(|child| child.flatten_inner(fmt, delimiter.as_opt_str(), mark_disamb))
             .collect();
         if xs.is_empty() {
             return None;
@@ -500,12 +547,12 @@ impl IrSeq {
         Some(grp)
     }
 
-    fn append_edges(
+    fn append_edges<O: OutputFormat<Output = SmartString>>(
         &self,
         node: NodeId,
-        arena: &IrArena<Markup>,
+        arena: &IrArena<O>,
         edges: &mut Vec<EdgeData>,
-        fmt: &Markup,
+        fmt: &O,
         format_context: Formatting,
         override_delim: Option<&str>,
     ) {
@@ -0,0 +1,115 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2020 Corporation for Digital Scholarship
+
+//! A serializable view of a cite's IR tree, for snapshot tests and bug reports.
+
+use super::{CiteEdgeData, IR};
+use crate::db::IrGen;
+use crate::prelude::*;
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use std::sync::Arc;
+
+/// A cite's IR tree, which serializes as nested objects with a `type`, the node's `groupVars`,
+/// the fields that affect how it renders, and its `children`. Rendered content is written out as
+/// the formatter's output.
+pub struct IrJson {
+    pub(crate) gen: Arc<IrGen>,
+    pub(crate) fmt: Markup,
+}
+
+impl Serialize for IrJson {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        NodeJson {
+            tree: self.gen.tree_ref(),
+            fmt: &self.fmt,
+        }
+        .serialize(serializer)
+    }
+}
+
+struct NodeJson<'a> {
+    tree: IrTreeRef<'a, Markup>,
+    fmt: &'a Markup,
+}
+
+fn edge_kind(edge: &CiteEdgeData<Markup>) -> &'static str {
+    match edge {
+        CiteEdgeData::Title(_) => "Title",
+        CiteEdgeData::Output(_) => "Output",
+        CiteEdgeData::Locator(_) => "Locator",
+        CiteEdgeData::LocatorLabel(_) => "LocatorLabel",
+        CiteEdgeData::YearSuffix(_) => "YearSuffix",
+        CiteEdgeData::CitationNumber(_) => "CitationNumber",
+        CiteEdgeData::CitationNumberLabel(_) => "CitationNumberLabel",
+        CiteEdgeData::Frnn(_) => "Frnn",
+        CiteEdgeData::FrnnLabel(_) => "FrnnLabel",
+        CiteEdgeData::Accessed(_) => "Accessed",
+        CiteEdgeData::Year(_) => "Year",
+        CiteEdgeData::Term(_) => "Term",
+    }
+}
+
+impl<'a> Serialize for NodeJson<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (ir, gv) = match self.tree.get_node() {
+            Some(node) => node.get(),
+            None => return serializer.serialize_none(),
+        };
+        let mut map = serializer.serialize_map(None)?;
+        let kind = match ir {
+            IR::Rendered(_) => "Rendered",
+            IR::Name(_) => "Name",
+            IR::Substitute => "Substitute",
+            IR::ConditionalDisamb(_) => "ConditionalDisamb",
+            IR::YearSuffix(_) => "YearSuffix",
+            IR::Seq(_) => "Seq",
+            IR::NameCounter(_) => "NameCounter",
+        };
+        map.serialize_entry("type", kind)?;
+        map.serialize_entry("groupVars", &format!("{:?}", gv))?;
+        match ir {
+            IR::Rendered(edge) => {
+                map.serialize_entry("edge", &edge.as_ref().map(edge_kind))?;
+                let output = edge
+                    .as_ref()
+                    .map(|edge| self.fmt.output(edge.inner(), false));
+                map.serialize_entry("output", &output.as_deref())?;
+            }
+            IR::ConditionalDisamb(c) => {
+                map.serialize_entry("done", &c.done)?;
+            }
+            IR::YearSuffix(ys) => {
+                map.serialize_entry("suffixNum", &ys.suffix_num)?;
+            }
+            IR::Seq(seq) => {
+                map.serialize_entry("delimiter", &seq.delimiter.as_deref())?;
+                let affixes = seq.affixes.as_ref();
+                map.serialize_entry("prefix", &affixes.map(|a| a.prefix.as_str()))?;
+                map.serialize_entry("suffix", &affixes.map(|a| a.suffix.as_str()))?;
+                let display = seq.display.as_ref().map(AsRef::<str>::as_ref);
+                map.serialize_entry("display", &display)?;
+                let formatting = seq.formatting.map(|f| format!("{:?}", f));
+                map.serialize_entry("formatting", &formatting)?;
+                let dropped = seq.dropped_gv.map(|gv| format!("{:?}", gv));
+                map.serialize_entry("droppedGroupVars", &dropped)?;
+                map.serialize_entry("isLayout", &seq.is_layout)?;
+            }
+            IR::Name(_) | IR::Substitute | IR::NameCounter(_) => {}
+        }
+        let children: Vec<_> = self
+            .tree
+            .children()
+            .map(|tree| NodeJson {
+                tree,
+                fmt: self.fmt,
+            })
+            .collect();
+        if !children.is_empty() {
+            map.serialize_entry("children", &children)?;
+        }
+        map.end()
+    }
+}
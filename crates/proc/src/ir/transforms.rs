@@ -59,14 +59,34 @@ impl<O: OutputFormat> IR<O> {
         // Pull off the first field of self -> [first, ...rest]
 
         if node.children(arena).take(2).count() != 2 {
-            return None;
+            // A layout with a single group or choose in it: split that instead, and lift the two
+            // halves up into this node.
+            let only = node.children(arena).next()?;
+            match arena.get(only)?.get().0 {
+                IR::Seq(_) | IR::ConditionalDisamb(_) => {}
+                _ => return None,
+            }
+            let inner = IR::split_first_field(only, arena)?;
+            let inner_formatting = match &arena.get(inner)?.get().0 {
+                IR::Seq(seq) => seq.formatting,
+                _ => None,
+            };
+            let halves: Vec<NodeId> = inner.children(arena).collect();
+            for half in halves {
+                if let IR::Seq(seq) = &mut arena.get_mut(half)?.get_mut().0 {
+                    seq.formatting = inner_formatting;
+                }
+                half.detach(arena);
+                node.append(half, arena);
+            }
+            return Some(node);
         }
 
-        // Steal the top seq's IrSeq configuration
-        let orig_top = if let (IR::Seq(s), gv) = arena.get_mut(node)?.get_mut() {
-            (mem::take(s), *gv)
-        } else {
-            return None;
+        // Steal the top seq's IrSeq configuration. A choose branch has none of its own.
+        let orig_top = match arena.get_mut(node)?.get_mut() {
+            (IR::Seq(s), gv) => (mem::take(s), *gv),
+            (IR::ConditionalDisamb(_), gv) => (IrSeq::default(), *gv),
+            _ => return None,
         };
 
         // Detach the first child
@@ -168,6 +188,24 @@ impl<'a, O: OutputFormat> IrTreeRef<'a, O> {
         }
     }
 
+    /// Every names block in the tree, in document order. Like `first_names_block`, this returns
+    /// arena `NodeId`s; the `NameIR`s live in the arena, not behind their own locks.
+    pub fn all_names_blocks(&self) -> Vec<NodeId> {
+        let mut blocks = Vec::new();
+        self.collect_names_blocks(&mut blocks);
+        blocks
+    }
+
+    fn collect_names_blocks(&self, blocks: &mut Vec<NodeId>) {
+        match self.get_node().map(|x| &x.get().0) {
+            Some(IR::Name(_)) => blocks.push(self.node),
+            Some(IR::ConditionalDisamb(_)) | Some(IR::Seq(_)) | Some(IR::Substitute) => self
+                .children()
+                .for_each(|child| child.collect_names_blocks(blocks)),
+            _ => {}
+        }
+    }
+
     fn find_first_year(&self) -> Option<NodeId> {
         match &self.get_node()?.get().0 {
             IR::Rendered(Some(CiteEdgeData::Year(_b))) => Some(self.node),
@@ -210,7 +248,7 @@ impl<'a, O: OutputFormat> IrTreeRef<'a, O> {
         }
     }
 
-    /// Rendered(None), empty YearSuffix or empty seq
+    /// Rendered(None), empty YearSuffix or empty seq, or a name counter that found no names
     pub fn is_empty(&self) -> bool {
         let me = match self.get_node() {
             Some(x) => x.get(),
@@ -223,7 +261,10 @@ impl<'a, O: OutputFormat> IrTreeRef<'a, O> {
             | IR::ConditionalDisamb(_)
             | IR::YearSuffix(_)
             | IR::Substitute => self.children().next().is_none(),
-            IR::NameCounter(_nc) => false,
+            IR::NameCounter(nc) => {
+                nc.name_irs.iter().all(|nir| nir.disamb_names.is_empty())
+                    || self.children().next().is_none()
+            }
         }
     }
 
@@ -271,30 +312,30 @@ impl<'a, O: OutputFormat> IrTreeMut<'a, O> {
             }
             IR::ConditionalDisamb(_) => None,
             IR::Seq(_) | IR::Substitute => {
-                let mut iter = self.node.children(self.arena).fuse();
-                let first_two = (iter.next(), iter.next());
-                // Check for the exact explicit year suffix IR output
-                let mut found = if iter.next().is_some() {
-                    None
-                } else if let (Some(first), Some(second)) = first_two {
-                    match self.arena.get(second).unwrap().get() {
-                        (IR::YearSuffix(_), GroupVars::Unresolved) if has_explicit => {
-                            self.with_node(first, |f| f.suppress_first_year(has_explicit))
+                let child_ids: Vec<_> = self.node.children(self.arena).collect();
+                // Check for the exact year suffix IR output, wherever it is in the seq, and
+                // suppress the closest year before it.
+                let ysuf_ix = child_ids.iter().position(|&child| {
+                    match self.arena.get(child).unwrap().get() {
+                        (IR::YearSuffix(_), GroupVars::Unresolved) => has_explicit,
+                        (IR::YearSuffix(_), GroupVars::Important) => {
+                            !has_explicit && !self.tree_at_node(child).is_empty()
                         }
-                        (IR::YearSuffix(_), GroupVars::Important)
-                            if !has_explicit && !self.tree_at_node(second).is_empty() =>
-                        {
-                            self.with_node(first, |f| f.suppress_first_year(has_explicit))
+                        _ => false,
+                    }
+                });
+                let mut found = None;
+                if let Some(ix) = ysuf_ix {
+                    for &before in child_ids[..ix].iter().rev() {
+                        found = self.with_node(before, |f| f.suppress_first_year(has_explicit));
+                        if found.is_some() {
+                            break;
                         }
-                        _ => None,
                     }
-                } else {
-                    None
-                };
+                }
 
                 // Otherwise keep looking in subtrees etc
                 if found.is_none() {
-                    let child_ids: Vec<_> = self.node.children(self.arena).collect();
                     for child in child_ids {
                         found = self.with_node(child, |ch| ch.suppress_first_year(has_explicit));
                         if found.is_some() {
@@ -599,7 +640,7 @@ impl<'a, T> ReducedNameToken<'a, T> {
             NameToken::Ellipsis => ReducedNameToken::Ellipsis,
             NameToken::EtAl(..) => ReducedNameToken::EtAl,
             NameToken::Space => ReducedNameToken::Space,
-            NameToken::Delimiter => ReducedNameToken::Delimiter,
+            NameToken::Delimiter | NameToken::EtAlDelimiter => ReducedNameToken::Delimiter,
             NameToken::And => ReducedNameToken::And,
         }
     }
@@ -1112,6 +1153,128 @@ fn test_left_right_layout() {
     );
 }
 
+#[test]
+fn test_suppress_year_before_suffix() {
+    let mut arena = IrArena::<Markup>::new();
+    let fmt = Markup::html();
+
+    let seq = arena.seq(
+        IrSeq {
+            delimiter: Some(" ".into()),
+            ..Default::default()
+        },
+        |arena, seq| {
+            let original = arena.blob(CiteEdgeData::Year(fmt.plain("1990")), GroupVars::Important);
+            let year = arena.blob(CiteEdgeData::Year(fmt.plain("2000")), GroupVars::Important);
+            let ysuf = arena.new_node((
+                IR::YearSuffix(YearSuffix {
+                    hook: YearSuffixHook::Plain,
+                    suffix_num: Some(1),
+                }),
+                GroupVars::Important,
+            ));
+            let suffix = arena.blob(
+                CiteEdgeData::YearSuffix(fmt.plain("a")),
+                GroupVars::Important,
+            );
+            ysuf.append(suffix, arena);
+            let title = arena.blob(
+                CiteEdgeData::Output(fmt.plain("title")),
+                GroupVars::Important,
+            );
+            seq.append(original, arena);
+            seq.append(year, arena);
+            seq.append(ysuf, arena);
+            seq.append(title, arena);
+        },
+    );
+    let mut tree = IrTree::new(seq, arena);
+    tree.suppress_year();
+
+    // The year next to the suffix goes, not the first year in the seq.
+    let flat = tree.tree_ref().flatten(&fmt, None).unwrap();
+    assert_eq!(&fmt.output(flat, false), "1990 a title");
+}
+
+#[test]
+fn test_name_counter_deep_equals() {
+    let fmt = Markup::html();
+    let counter = |arena: &mut IrArena<Markup>, count: &str| {
+        let nc = IrNameCounter {
+            name_irs: Vec::new(),
+            group_vars: GroupVars::Important,
+        };
+        let node = arena.new_node((IR::NameCounter(nc), GroupVars::Important));
+        let rendered = arena.blob(CiteEdgeData::Output(fmt.plain(count)), GroupVars::Important);
+        node.append(rendered, arena);
+        node
+    };
+    let mut arena = IrArena::<Markup>::new();
+    let three = counter(&mut arena, "3");
+    let also_three = counter(&mut arena, "3");
+    let two = counter(&mut arena, "2");
+    let equals = |a: NodeId, b: NodeId| {
+        let (ai, ag) = arena.get(a).unwrap().get();
+        let (bi, bg) = arena.get(b).unwrap().get();
+        ai.deep_equals(*ag, a, &arena, bi, *bg, b, &arena)
+    };
+    assert!(equals(three, also_three));
+    assert!(!equals(three, two));
+}
+
+#[test]
+fn test_conditional_disamb_inherits_delimiter() {
+    use csl::{Choose, Conditions, Else, IfThen, Match};
+    let mut arena = IrArena::<Markup>::new();
+    let fmt = Markup::html();
+
+    let choose = Arc::new(Choose(
+        IfThen(Conditions(Match::Any, Vec::new()), Vec::new()),
+        Vec::new(),
+        Else(Vec::new()),
+    ));
+    let seq = arena.seq(
+        IrSeq {
+            delimiter: Some(", ".into()),
+            ..Default::default()
+        },
+        |arena, seq| {
+            let a = arena.blob(CiteEdgeData::Output(fmt.plain("a")), GroupVars::Important);
+            let cond = arena.new_node((
+                IR::ConditionalDisamb(ConditionalDisambIR {
+                    choose,
+                    done: false,
+                }),
+                GroupVars::Important,
+            ));
+            // A choose branch takes the delimiter of the group around the choose
+            let branch = arena.seq(
+                IrSeq {
+                    should_inherit_delim: true,
+                    ..Default::default()
+                },
+                |arena, branch| {
+                    let b = arena.blob(CiteEdgeData::Output(fmt.plain("b")), GroupVars::Important);
+                    let c = arena.blob(CiteEdgeData::Output(fmt.plain("c")), GroupVars::Important);
+                    branch.append(b, arena);
+                    branch.append(c, arena);
+                },
+            );
+            cond.append(branch, arena);
+            seq.append(a, arena);
+            seq.append(cond, arena);
+        },
+    );
+    let tree = IrTreeRef::new(seq, &arena);
+    let flat = tree.flatten(&fmt, None).unwrap();
+    assert_eq!(&fmt.output(flat, false), "a, b, c");
+    let out = |s: &str| EdgeData::<SmartString>::Output(s.into());
+    assert_eq!(
+        tree.to_edge_stream(&fmt),
+        vec![out("a"), out(", "), out("b"), out(", "), out("c")]
+    );
+}
+
 #[cfg(test)]
 trait ArenaExtensions<O: OutputFormat> {
     fn blob(&mut self, edge: CiteEdgeData<O>, gv: GroupVars) -> NodeId;
@@ -40,14 +40,16 @@ mod number;
 mod page_range;
 mod ref_ir;
 mod renderer;
+mod required;
 mod sort;
 mod tree;
 mod walker;
 
-pub use crate::cluster::built_cluster_before_output;
-pub use crate::db::bib_item_preview;
+pub use crate::cluster::{built_cluster_before_output, cluster_delimiters, would_collapse_change};
 pub use crate::db::safe_default;
-pub use crate::sort::BibNumber;
+pub use crate::db::{bib_item_preview, bib_item_split};
+pub use crate::required::missing_required_fields;
+pub use crate::sort::{sort_key_string, BibNumber};
 
 pub(crate) mod prelude {
     pub(crate) trait AsRefOptStr {
@@ -104,7 +106,8 @@ use prelude::*;
 #[cfg(test)]
 mod test;
 
-pub use self::ir::IR;
+pub use self::ir::{DisambPass, IrJson, IR};
+pub use crate::prelude::{GroupVars, IrArena, IrSeq, IrSum, NodeId};
 
 // TODO: function to walk the entire tree for a <text variable="year-suffix"> to work out which
 // nodes are possibly disambiguate-able in year suffix mode and if such a node should be inserted
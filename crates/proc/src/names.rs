@@ -6,6 +6,7 @@
 
 use self::initials::initialize;
 
+use crate::choose::CondChecker;
 use crate::disamb::names::{
     self as disamb, DisambNameData, DisambNameRatchet, NameIR, PersonDisambNameRatchet,
 };
@@ -30,6 +31,7 @@ impl<B> DisambNameRatchet<B> {
         primary: bool,
         all_same_family_name: bool,
         advance_to_global: bool,
+        position: Option<Position>,
     ) -> Self {
         let mut data = DisambNameData {
             var,
@@ -49,7 +51,7 @@ impl<B> DisambNameRatchet<B> {
                 // optimise: should apply pass to the ratchet's iterator as well
             }
         }
-        let ratchet = PersonDisambNameRatchet::new(&db.style(), id, data);
+        let ratchet = PersonDisambNameRatchet::new(&db.style(), id, data, position);
         DisambNameRatchet::Person(ratchet)
     }
 }
@@ -67,6 +69,7 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
     let style = ctx.style();
     let locale = ctx.locale();
     let refr = ctx.reference();
+    let position = ctx.position();
     let get_name_ir = move |(var, label_var, value): (NameVariable, NameVariable, Vec<Name>)| {
         // fullstyles_APA.txt
         let all_same_family_name = disamb::all_same_family_name(&value);
@@ -86,6 +89,7 @@ pub fn to_individual_name_irs<'a, O: OutputFormat, I: OutputFormat>(
                         primary,
                         all_same_family_name,
                         advance_to_global,
+                        position,
                     ),
                     Name::Literal {
                         literal,
@@ -206,6 +210,7 @@ pub(crate) fn sort_strings_for_names(
         bump_name_count: 0,
         demote_non_dropping_particle: style.demote_non_dropping_particle,
         initialize_with_hyphen: style.initialize_with_hyphen,
+        is_english: crate::cite_context::is_english(&style, refr.language.as_ref()),
         fmt: &fmt,
     };
     let mut out = Vec::new();
@@ -438,6 +443,7 @@ impl<'c, O: OutputFormat> NameIR<O> {
             bump_name_count: self.name_counter.bump,
             demote_non_dropping_particle: self.demote_non_dropping_particle,
             initialize_with_hyphen: self.initialize_with_hyphen,
+            is_english: self.is_english,
         }
     }
 
@@ -669,6 +675,8 @@ pub enum NameToken {
     EtAl(SmartString, Option<Formatting>),
     Ellipsis,
     Delimiter,
+    /// The delimiter before an et-al term, which `<et-al delimiter="...">` can override
+    EtAlDelimiter,
     And,
     Space,
 }
@@ -680,6 +688,8 @@ pub struct OneNameVar<'a, O: OutputFormat> {
     // From Style
     pub demote_non_dropping_particle: DemoteNonDroppingParticle,
     pub initialize_with_hyphen: bool,
+    /// Whether the reference is in English, for title-casing name parts
+    pub is_english: bool,
     pub fmt: &'a O,
 }
 
@@ -754,7 +764,7 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
                             .delimiter_precedes_et_al
                             .unwrap_or(DelimiterPrecedes::Contextual);
                         if should_delimit_after(dpea, self, ea_use_first) {
-                            nms.push(NameToken::Delimiter);
+                            nms.push(NameToken::EtAlDelimiter);
                         } else {
                             nms.push(NameToken::Space);
                         }
@@ -901,6 +911,7 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
         // want InlineElement::Text but with text-casing applied.
         let mut options = IngestOptions {
             no_parse_quotes: true,
+            is_english: self.is_english,
             ..Default::default()
         };
         match o_part {
@@ -955,7 +966,22 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
                             },
                             self.initialize_with_hyphen,
                         );
-                        parts.push(self.format_with_part(given_part, initialized.as_ref()));
+                        // Initials already have the case they need; re-casing them would only
+                        // mangle them, e.g. lowercase turning "J. R." into "j. r."
+                        let initials_only = pn.family.is_some()
+                            && self.name_el.initialize_with.is_some()
+                            && self.name_el.initialize.unwrap_or(true);
+                        let initials_part;
+                        let first_part = if initials_only {
+                            initials_part = given_part.as_ref().map(|p| NamePart {
+                                text_case: TextCase::None,
+                                ..p.clone()
+                            });
+                            &initials_part
+                        } else {
+                            given_part
+                        };
+                        parts.push(self.format_with_part(first_part, initialized.as_ref()));
                         if token != NamePartToken::Given {
                             if let Some(dp) = pn.dropping_particle.as_ref() {
                                 parts.push(fmt.plain(" "));
@@ -1016,6 +1042,7 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
                     let options = IngestOptions {
                         no_parse_quotes: true,
                         text_case: family_part.as_ref().map_or(TextCase::None, |p| p.text_case),
+                        is_english: self.is_english,
                         ..Default::default()
                     };
                     fmt.apply_text_case(&mut casing, &options);
@@ -1104,7 +1131,7 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
         &'a self,
         names_slice: &[DisambNameRatchet<O::Build>],
         position: Position,
-        _et_al: &Option<NameEtAl>,
+        et_al: &Option<NameEtAl>,
         is_sort_key: bool,
         and_term: Option<&SmartString>,
         etal_term: Option<&(SmartString, Option<Formatting>)>,
@@ -1118,6 +1145,7 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
         });
 
         let and_term = and_term.cloned();
+        let et_al_delimiter = et_al.as_ref().and_then(|e| e.delimiter.clone());
 
         let iterator = name_tokens.into_iter().filter_map(move |n| {
             Some(match n {
@@ -1126,6 +1154,13 @@ impl<'a, O: OutputFormat> OneNameVar<'a, O> {
                     let s = self.name_el.delimiter.as_opt_str().unwrap_or(", ");
                     NameTokenBuilt::Built(fmt.plain(s), citeproc_io::unicode::is_latin_cyrillic(s))
                 }
+                NameToken::EtAlDelimiter => {
+                    let s = et_al_delimiter
+                        .as_opt_str()
+                        .or_else(|| self.name_el.delimiter.as_opt_str())
+                        .unwrap_or(", ");
+                    NameTokenBuilt::Built(fmt.plain(s), citeproc_io::unicode::is_latin_cyrillic(s))
+                }
                 NameToken::EtAl(text, formatting) => {
                     if is_sort_key {
                         return None;
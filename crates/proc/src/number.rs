@@ -64,16 +64,25 @@ fn get_ampersand(locale: &Locale) -> &str {
     }
 }
 
+/// The locale's symbol for a range term, or an en-dash if it doesn't have one.
+fn get_range_term(locale: &Locale, term: MiscTerm) -> &str {
+    let sel = SimpleTermSelector::Misc(term, TermFormExtended::Symbol);
+    locale
+        .get_simple_term(sel)
+        .map(|amp| amp.singular().trim())
+        .unwrap_or("\u{2013}")
+}
+
+/// Joins the ends of a collapsed range of cites, like `[1–3]`. This is the
+/// `citation-range-delimiter` term, not the one used for page ranges.
+pub fn get_range_delimiter(locale: &Locale) -> &str {
+    get_range_term(locale, MiscTerm::CitationRangeDelimiter)
+}
+
 pub fn get_hyphen(locale: &Locale, variable: NumberVariable) -> &str {
     // A few more than the spec's list of en-dashable variables
     // https://github.com/Juris-M/citeproc-js/blob/1aa49dd2ab9a1c85d3060073780d65c86754a438/src/util_number.js#L584
-    let get = |term: MiscTerm| {
-        let sel = SimpleTermSelector::Misc(term, TermFormExtended::Symbol);
-        locale
-            .get_simple_term(sel)
-            .map(|amp| amp.singular().trim())
-            .unwrap_or("\u{2013}")
-    };
+    let get = |term: MiscTerm| get_range_term(locale, term);
     match variable {
         NumberVariable::Page
         | NumberVariable::Locator
@@ -90,6 +99,7 @@ pub fn get_hyphen(locale: &Locale, variable: NumberVariable) -> &str {
 fn test_get_hyphen() {
     let loc = &Locale::default();
     assert_eq!(get_hyphen(loc, NumberVariable::Locator), "\u{2013}");
+    assert_eq!(get_range_delimiter(loc), "\u{2013}");
 }
 
 pub fn arabic_number(
@@ -133,6 +143,20 @@ enum State<'a> {
     Normal,
     Hyphenating { prefix: &'a str, last: NumBefore },
 }
+/// The width of a number whose prefix is only leading zeros, like `007`. Limited to widths
+/// where the padded number still fits in a u32.
+fn zero_padded_width(pfx: &str, num: u32) -> Option<u32> {
+    if !pfx.bytes().all(|b| b == b'0') {
+        return None;
+    }
+    let width = pfx.len() + num.to_string().len();
+    if width <= 9 {
+        Some(width as u32)
+    } else {
+        None
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 enum HyphenInsert {
     None,
@@ -147,24 +171,25 @@ impl<'a> State<'a> {
         is_roman: bool,
         pfx: &'a str,
         sfx: &'a str,
-    ) -> (&'a str, u32, HyphenInsert, Self) {
-        use crate::page_range::truncate_prf;
+    ) -> (&'a str, SmartString, HyphenInsert, Self) {
+        use crate::page_range::format_range_end;
+        let whole = || -> SmartString { num.to_string().into() };
         match *self {
             State::Normal if sfx.is_empty() => (
                 pfx,
-                num,
+                whole(),
                 HyphenInsert::None,
                 State::Hyphenating {
                     prefix: pfx,
                     last: NumBefore::see_num(num, is_roman),
                 },
             ),
-            State::Normal => (pfx, num, HyphenInsert::None, State::Normal),
+            State::Normal => (pfx, whole(), HyphenInsert::None, State::Normal),
             State::Hyphenating { prefix, last } if pfx == prefix => {
                 // Prefixes match, we're going to crop it
                 if let Some(last_num) = last.matching_for_crop(is_roman) {
                     if let Some(prf) = prf {
-                        let cropped = truncate_prf(prf, last_num, num);
+                        let cropped = format_range_end(prf, last_num, num);
                         let crop_prefix = match prf {
                             PageRangeFormat::Expanded => pfx,
                             _ => "",
@@ -173,14 +198,36 @@ impl<'a> State<'a> {
                     } else {
                         // The spec says this should be HyphenInsert::Simple, but it breaks
                         // ~ a hundred CSL tests, so the spec is to be ignored...
-                        (pfx, num, HyphenInsert::Locale, State::Normal)
+                        (pfx, whole(), HyphenInsert::Locale, State::Normal)
                     }
                 } else {
-                    (pfx, num, HyphenInsert::Simple, State::Normal)
+                    (pfx, whole(), HyphenInsert::Simple, State::Normal)
                 }
             }
-            State::Hyphenating { prefix: _, last: _ } => {
-                (pfx, num, HyphenInsert::Simple, State::Normal)
+            State::Hyphenating { prefix, last } => {
+                // Zero-padded numbers (007-012) parse with the padding as a prefix, so the
+                // prefixes differ. If both sides are the same width, crop the padded numbers.
+                let padded = last.matching_for_crop(is_roman).and_then(|last_num| {
+                    let width = zero_padded_width(prefix, last_num)?;
+                    if zero_padded_width(pfx, num)? == width && sfx.is_empty() {
+                        Some((last_num, width))
+                    } else {
+                        None
+                    }
+                });
+                match (prf, padded) {
+                    (Some(prf), Some((last_num, width))) => {
+                        let base = 10u32.pow(width);
+                        let cropped = format_range_end(prf, base + last_num, base + num);
+                        if cropped.len() > width as usize {
+                            (pfx, whole(), HyphenInsert::Locale, State::Normal)
+                        } else {
+                            ("", cropped, HyphenInsert::Locale, State::Normal)
+                        }
+                    }
+                    (None, Some(_)) => (pfx, whole(), HyphenInsert::Locale, State::Normal),
+                    _ => (pfx, whole(), HyphenInsert::Simple, State::Normal),
+                }
             }
         }
     }
@@ -303,6 +350,23 @@ fn tokens_to_string(
     s
 }
 
+/// The first run of digits in a value like `Volume 3`, if there is one.
+pub fn extract_numeric(value: &str) -> Option<&str> {
+    let start = value.find(|c: char| c.is_ascii_digit())?;
+    let rest = &value[start..];
+    let len = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or_else(|| rest.len());
+    Some(&rest[..len])
+}
+
+#[test]
+fn test_extract_numeric() {
+    assert_eq!(extract_numeric("Volume 3"), Some("3"));
+    assert_eq!(extract_numeric("no. 12, part 2"), Some("12"));
+    assert_eq!(extract_numeric("Special issue"), None);
+}
+
 /// Numbers bigger than 3999 are too cumbersome anyway
 pub fn roman_representable(val: &NumericValue) -> bool {
     match val {
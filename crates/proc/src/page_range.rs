@@ -1,33 +1,98 @@
+use citeproc_io::SmartString;
 use csl::PageRangeFormat;
 
-/// Returns the second number with the page range format applied.
+/// Returns the second number with the page range format applied. As a `u32` this drops any
+/// leading zeros, so `MinimalTwo` for `100, 104` gives `4`; use `format_range_end` to write it.
 pub fn truncate_prf(prf: PageRangeFormat, first: u32, mut second: u32) -> u32 {
     second = expand(first, second);
     match prf {
-        PageRangeFormat::Chicago => {
-            let mod100 = first % 100;
-            let delta = second - first;
-            if first < 100 || mod100 == 0 {
-                second
-            } else if mod100 < 10 && delta < 90 {
-                truncate_diff(first, second, 1)
-            } else if closest_smaller_power_of_10(first) == 1000 {
-                let chopped = truncate_diff(first, second, 2);
-                if closest_smaller_power_of_10(chopped) == 100 {
-                    // force 4 digits if 3 are different
-                    return truncate_diff(first, second, 4);
-                }
-                chopped
-            } else {
-                truncate_diff(first, second, 2)
-            }
-        }
+        PageRangeFormat::Chicago => chicago(first, second),
         PageRangeFormat::Minimal => truncate_diff(first, second, 1),
         PageRangeFormat::MinimalTwo => truncate_diff(first, second, 2),
         PageRangeFormat::Expanded => second,
     }
 }
 
+/// Writes the second number of a page or locator range with the page range format applied,
+/// keeping the zeros that `MinimalTwo` needs, so `100, 104` becomes `04`.
+pub fn format_range_end(prf: PageRangeFormat, first: u32, second: u32) -> SmartString {
+    let expanded = expand(first, second).to_string();
+    let cropped = truncate_prf(prf, first, second);
+    // The shortest tail of the expanded number that is the cropped number
+    let mut keep = (1..=expanded.len())
+        .find(|&k| expanded[expanded.len() - k..].parse::<u32>().ok() == Some(cropped))
+        .unwrap_or_else(|| expanded.len());
+    if prf == PageRangeFormat::MinimalTwo {
+        keep = keep.max(2.min(expanded.len()));
+    }
+    expanded[expanded.len() - keep..].into()
+}
+
+#[test]
+fn format_range_end_modes() {
+    use PageRangeFormat::*;
+    assert_eq!(format_range_end(Expanded, 321, 325), "325");
+    assert_eq!(format_range_end(Minimal, 321, 325), "5");
+    assert_eq!(format_range_end(MinimalTwo, 321, 325), "25");
+    assert_eq!(format_range_end(Chicago, 321, 325), "25");
+    assert_eq!(format_range_end(Expanded, 100, 104), "104");
+    assert_eq!(format_range_end(Minimal, 100, 104), "4");
+    assert_eq!(format_range_end(MinimalTwo, 100, 104), "04");
+    assert_eq!(format_range_end(Chicago, 100, 104), "104");
+    // abbreviated input is expanded first
+    assert_eq!(format_range_end(Expanded, 321, 5), "325");
+}
+
+/// The Chicago Manual of Style rules for page ranges, given an already-expanded second number.
+///
+/// 1. Under 100, use all digits (3–10, 71–72)
+/// 2. Multiples of 100, use all digits (100–104, 1100–1123)
+/// 3. 101 through 109 in each hundred, use the changed part only (101–8, 1002–6)
+/// 4. 110 through 199 in each hundred, use two or more digits as needed (321–25, 11564–68)
+/// 5. If four digits and three of them change, use all four (1496–1504)
+pub fn chicago(first: u32, second: u32) -> u32 {
+    let mod100 = first % 100;
+    let delta = second.saturating_sub(first);
+    if first < 100 || mod100 == 0 {
+        second
+    } else if mod100 < 10 && delta < 90 {
+        truncate_diff(first, second, 1)
+    } else if closest_smaller_power_of_10(first) == 1000 {
+        let chopped = truncate_diff(first, second, 2);
+        if closest_smaller_power_of_10(chopped) == 100 {
+            // force 4 digits if 3 are different
+            return truncate_diff(first, second, 4);
+        }
+        chopped
+    } else {
+        truncate_diff(first, second, 2)
+    }
+}
+
+#[test]
+fn truncate_prf_modes() {
+    use PageRangeFormat::*;
+    assert_eq!(truncate_prf(Expanded, 321, 325), 325);
+    assert_eq!(truncate_prf(Minimal, 321, 325), 5);
+    assert_eq!(truncate_prf(MinimalTwo, 321, 325), 25);
+    assert_eq!(truncate_prf(Chicago, 321, 325), 25);
+    assert_eq!(truncate_prf(Expanded, 100, 104), 104);
+    assert_eq!(truncate_prf(Minimal, 100, 104), 4);
+    // the leading zero of "04" is lost; format_range_end keeps it
+    assert_eq!(truncate_prf(MinimalTwo, 100, 104), 4);
+    assert_eq!(truncate_prf(Chicago, 100, 104), 104);
+    // abbreviated input is expanded first
+    assert_eq!(truncate_prf(Expanded, 321, 5), 325);
+}
+
+#[test]
+fn chicago_canonical() {
+    assert_eq!(chicago(100, 104), 104);
+    assert_eq!(chicago(321, 325), 25);
+    assert_eq!(chicago(1496, 1504), 1504);
+    assert_eq!(chicago(11564, 11568), 68);
+}
+
 #[test]
 fn page_range_chicago() {
     fn go(a: u32, b: u32) -> u32 {
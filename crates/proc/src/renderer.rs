@@ -1,13 +1,16 @@
 use crate::cite_context::RenderContext;
-use crate::number::{arabic_number, render_ordinal, roman_lower, roman_representable};
+use crate::number::{
+    arabic_number, extract_numeric, render_ordinal, roman_lower, roman_representable,
+};
 use crate::prelude::*;
+use citeproc_io::output::links::{Doi, LinkId, Pmcid, Pmid};
 use citeproc_io::output::LocalizedQuotes;
-use citeproc_io::{Name, NumericToken, NumericValue, Reference};
+use citeproc_io::{Locator, Name, NumericToken, NumericValue, Reference};
 use csl::{
     Features, GenderedTermSelector, LabelElement, Lang, Locale, LocatorType, NameLabel,
     NameVariable, NumberElement, NumberVariable, NumericForm, PageRangeFormat, Plural,
-    RoleTermSelector, SortKey, StandardVariable, Style, TextElement, TextTermSelector, Variable,
-    VariableForm,
+    RoleTermSelector, SortKey, StandardVariable, Style, TermForm, TextElement, TextTermSelector,
+    Variable, VariableForm,
 };
 
 use crate::choose::CondChecker;
@@ -60,6 +63,7 @@ impl<'a, O: OutputFormat, I: OutputFormat> CondChecker for GenericContext<'a, O,
         fn has_year_only(&self, dvar: DateVariable) -> bool;
         fn has_month_or_season(&self, dvar: DateVariable) -> bool;
         fn has_day(&self, dvar: DateVariable) -> bool;
+        fn has_flag(&self, flag: &str) -> bool;
     }
 }
 
@@ -73,12 +77,7 @@ impl<'a, O: OutputFormat, I: OutputFormat> GenericContext<'a, O, I> {
 
     /// https://docs.citationstyles.org/en/stable/specification.html#non-english-items
     pub fn is_english(&self) -> bool {
-        let sty = self.style();
-        let cite = self.cite_lang();
-        // Bit messy but matches the spec wording
-        // If a style doesn't have a default, it's en-US, which is English.
-        let default_is_english = sty.default_locale.as_ref().map_or(true, |x| x.is_english());
-        cite.map_or(default_is_english, |l| l.is_english())
+        crate::cite_context::is_english(self.style(), self.cite_lang())
     }
 
     /// For setting display="X" on elements, where this should only take effect in the
@@ -134,9 +133,15 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
         self.ctx.format()
     }
 
-    fn page_range_format(&self, var: NumberVariable) -> Option<PageRangeFormat> {
+    /// `element` is the element's own `page-range-format`, which takes precedence over the
+    /// style's.
+    fn page_range_format(
+        &self,
+        var: NumberVariable,
+        element: Option<PageRangeFormat>,
+    ) -> Option<PageRangeFormat> {
         let style = self.ctx.style();
-        style.page_range_format.filter(|_| {
+        element.or(style.page_range_format).filter(|_| {
             var == NumberVariable::Page
                 || (var == NumberVariable::Locator
                     && self
@@ -161,7 +166,7 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
     ) -> O::Build {
         let locale = self.ctx.locale();
         let fmt = self.fmt();
-        let prf = self.page_range_format(var);
+        let prf = self.page_range_format(var, None);
         use crate::sort::natural_sort;
         let affixes = Some(if var == NumberVariable::CitationNumber {
             natural_sort::citation_number_affixes()
@@ -189,7 +194,7 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
     pub fn number(&self, number: &NumberElement, val: &NumericValue<'_>) -> O::Build {
         let locale = self.ctx.locale();
         debug!("number {:?}", val);
-        let prf = self.page_range_format(number.variable);
+        let prf = self.page_range_format(number.variable, number.page_range_format);
         let string = if let NumericValue::Tokens(_s, ts, true) = val {
             match number.form {
                 NumericForm::Roman if roman_representable(&val) => {
@@ -210,6 +215,13 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
                 }
                 _ => arabic_number(val, locale, number.variable, prf),
             }
+        } else if number.form == NumericForm::Numeric
+            && self.ctx.style().features.number_extract_numeric
+        {
+            match extract_numeric(val.verbatim()) {
+                Some(digits) => digits.into(),
+                None => arabic_number(val, locale, number.variable, prf),
+            }
         } else {
             arabic_number(val, locale, number.variable, prf)
         };
@@ -253,6 +265,7 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
                 affixes: text.affixes.clone(),
                 text_case: text.text_case,
                 display: text.display,
+                page_range_format: text.page_range_format,
             };
             self.number(&number, val)
         } else {
@@ -275,6 +288,7 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
             quotes: self.quotes(),
             strip_periods: text.strip_periods,
             is_english: self.ctx.is_english(),
+            max_length: text.max_length,
             ..Default::default()
         };
         let hyper = match var {
@@ -284,6 +298,28 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
         self.render_text_el(value, text, &options, hyper)
     }
 
+    /// Renders each value of a repeatable variable without the element's affixes and
+    /// formatting, which are applied to the surrounding sequence instead.
+    pub fn text_variable_multi(
+        &self,
+        text: &TextElement,
+        var: Variable,
+        values: &[String],
+    ) -> Vec<O::Build> {
+        let inner = TextElement {
+            formatting: None,
+            affixes: None,
+            quotes: false,
+            display: None,
+            ..text.clone()
+        };
+        values
+            .iter()
+            .filter(|value| !value.is_empty())
+            .map(|value| self.text_variable(&inner, StandardVariable::Ordinary(var), value))
+            .collect()
+    }
+
     pub fn text_value(&self, text: &TextElement, value: &str) -> Option<O::Build> {
         if value.is_empty() {
             return None;
@@ -328,6 +364,37 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
         hyper: Option<Variable>,
     ) -> O::Build {
         let fmt = self.fmt();
+        // CSL 1.0.2 Appendix VI: a URL prefix on a linkable variable (e.g.
+        // `prefix="https://doi.org/"`) becomes part of the link, and is not rendered separately.
+        let url_prefix = text
+            .affixes
+            .as_ref()
+            .filter(|_| {
+                matches!(
+                    hyper,
+                    Some(Variable::DOI) | Some(Variable::PMCID) | Some(Variable::PMID)
+                )
+            })
+            .filter(|affixes| is_url_prefix(&affixes.prefix));
+        if let Some(affixes) = url_prefix {
+            // The value may itself carry a prefix (`doi:10.1/x`, `http://dx.doi.org/10.1/x`);
+            // only the bare identifier goes after the style's prefix.
+            let id = match hyper {
+                Some(Variable::DOI) => Doi::trim(string),
+                Some(Variable::PMCID) => Pmcid::trim(string),
+                Some(Variable::PMID) => Pmid::trim(string),
+                _ => string,
+            };
+            let full_url = format!("{}{}", affixes.prefix, id);
+            let mut b = fmt.try_link_full(&full_url, options);
+            b = fmt.with_format(b, text.formatting);
+            let suffix_only = Affixes {
+                prefix: Default::default(),
+                suffix: affixes.suffix.clone(),
+            };
+            b = fmt.affixed_quoted(b, Some(&suffix_only), self.quotes_if(text.quotes));
+            return fmt.with_display(b, text.display, self.ctx.in_bibliography());
+        }
         let mut b = self.try_link(string, options, hyper);
         b = fmt.with_format(b, text.formatting);
         b = fmt.affixed_quoted(b, text.affixes.as_ref(), self.quotes_if(text.quotes));
@@ -425,4 +492,37 @@ impl<'c, O: OutputFormat, I: OutputFormat> Renderer<'c, O, I> {
                 })
         })
     }
+
+    /// Appends the locators after a cite's first one, each with a short label pluralised for
+    /// its own value, e.g. `pp. 10, 15–17 & fig. 3`.
+    pub fn append_locators(&self, first: O::Build, rest: &[Locator]) -> O::Build {
+        let fmt = self.fmt();
+        let locale = self.ctx.locale();
+        let and_term = locale.and_term(None).unwrap_or("and");
+        let style = self.ctx.style();
+        let delimiter = style.citation.locator_delimiter.as_deref().unwrap_or(", ");
+        let options = IngestOptions {
+            quotes: self.quotes(),
+            is_english: self.ctx.is_english(),
+            ..Default::default()
+        };
+        rest.iter().fold(first, |acc, locator| {
+            let val = NumericValue::from_localized(and_term)(locator.value());
+            let value = arabic_number(&val, locale, NumberVariable::Locator, None);
+            let sel = GenderedTermSelector::Locator(locator.type_of(), TermForm::Short);
+            let plural = val.is_multiple(NumberVariable::Locator);
+            let string = match locale
+                .get_text_term(TextTermSelector::Gendered(sel), plural)
+                .filter(|x| !x.is_empty())
+            {
+                Some(label) => format!("{} {}", label, value),
+                None => value.to_string(),
+            };
+            fmt.join_delim(acc, delimiter, fmt.ingest(&string, &options))
+        })
+    }
+}
+
+fn is_url_prefix(prefix: &str) -> bool {
+    prefix.starts_with("https://") || prefix.starts_with("http://")
 }
@@ -0,0 +1,181 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright © 2018 Corporation for Digital Scholarship
+
+//! Lists the fields a style would render for a reference but the reference does not have, so a
+//! host can warn about incomplete data before it shows up as a gap in a cite.
+
+use crate::choose::CondChecker;
+use crate::cite_context::ref_has_variable;
+use crate::prelude::*;
+use citeproc_io::{DateOrRange, Reference};
+use csl::*;
+
+/// Fields that are almost always expected when a style asks for them. Anything else a style
+/// references (volume, URL, publisher-place, ...) is routinely absent and not worth a warning.
+const REQUIRED: &[AnyVariable] = &[
+    AnyVariable::Name(NameVariable::Author),
+    AnyVariable::Ordinary(Variable::Title),
+    AnyVariable::Ordinary(Variable::ContainerTitle),
+    AnyVariable::Ordinary(Variable::Publisher),
+    AnyVariable::Date(DateVariable::Issued),
+];
+
+/// Walks the citation and bibliography layouts, taking only the `<choose>` branches that match
+/// the reference's type, and returns the commonly-required variables (see `REQUIRED`) that the
+/// style references but the reference lacks. Variable tests are assumed to pass, so a field
+/// rendered under `<if variable="publisher">` still counts as referenced. A `<names>` element is
+/// satisfied by any of its variables or any of the names in its `<substitute>`.
+///
+/// Returns an empty list if there is no such reference.
+pub fn missing_required_fields(db: &dyn IrDatabase, ref_id: Atom) -> Vec<AnyVariable> {
+    let refr = match db.reference(ref_id) {
+        Some(r) => r,
+        None => return Vec::new(),
+    };
+    let style = db.style();
+    let mut walker = RequiredWalker {
+        style: &style,
+        checker: TypeChecker {
+            refr: &refr,
+            features: &style.features,
+        },
+        missing: Vec::new(),
+    };
+    walker.walk_citation(&style);
+    walker.walk_bibliography(&style);
+    walker.missing
+}
+
+/// Evaluates type conditions against the reference, and treats every variable as present.
+struct TypeChecker<'a> {
+    refr: &'a Reference,
+    features: &'a Features,
+}
+
+impl<'a> CondChecker for TypeChecker<'a> {
+    fn has_variable(&self, _var: AnyVariable) -> bool {
+        true
+    }
+    fn is_numeric(&self, var: AnyVariable) -> bool {
+        match var {
+            AnyVariable::Number(v) => self
+                .refr
+                .number
+                .get(&v)
+                .map_or(false, |n| n.to_number().is_ok()),
+            _ => false,
+        }
+    }
+    fn is_disambiguate(&self, _current_count: u32) -> bool {
+        false
+    }
+    fn csl_type(&self) -> CslType {
+        self.refr.csl_type
+    }
+    fn locator_type(&self) -> Option<LocatorType> {
+        None
+    }
+    fn get_date(&self, dvar: DateVariable) -> Option<&DateOrRange> {
+        self.refr.date.get(&dvar)
+    }
+    fn position(&self) -> Option<Position> {
+        None
+    }
+    fn features(&self) -> &Features {
+        self.features
+    }
+    fn has_flag(&self, flag: &str) -> bool {
+        self.refr.flags.contains(flag)
+    }
+}
+
+struct RequiredWalker<'a> {
+    style: &'a Style,
+    checker: TypeChecker<'a>,
+    missing: Vec<AnyVariable>,
+}
+
+impl<'a> RequiredWalker<'a> {
+    fn require(&mut self, var: AnyVariable) {
+        if REQUIRED.contains(&var)
+            && !ref_has_variable(self.checker.refr, var)
+            && !self.missing.contains(&var)
+        {
+            self.missing.push(var);
+        }
+    }
+
+    /// Every names variable in a substitute, including those in macros and groups
+    fn substitute_names(&self, elements: &[Element], out: &mut Vec<NameVariable>) {
+        for el in elements {
+            match el {
+                Element::Names(names) => out.extend(names.variables.iter().cloned()),
+                Element::Group(group) => self.substitute_names(&group.elements, out),
+                Element::Text(TextElement {
+                    source: TextSource::Macro(name),
+                    ..
+                }) => {
+                    if let Some(els) = self.style.macros.get(name) {
+                        self.substitute_names(els, out);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<'a> StyleWalker for RequiredWalker<'a> {
+    type Output = ();
+    type Checker = TypeChecker<'a>;
+
+    fn default(&mut self) -> Self::Output {}
+
+    fn get_checker(&self) -> Option<&Self::Checker> {
+        Some(&self.checker)
+    }
+
+    fn text_macro(&mut self, text: &TextElement, name: &SmartString) -> Self::Output {
+        let style = self.style;
+        if let Some(elements) = style.macros.get(name) {
+            self.fold(elements, WalkerFoldType::Macro(text));
+        }
+    }
+
+    fn text_variable(
+        &mut self,
+        _text: &TextElement,
+        svar: StandardVariable,
+        _form: VariableForm,
+    ) -> Self::Output {
+        self.require((&svar).into());
+    }
+
+    fn number(&mut self, number: &NumberElement) -> Self::Output {
+        self.require(AnyVariable::Number(number.variable));
+    }
+
+    fn date(&mut self, date: &BodyDate) -> Self::Output {
+        self.require(AnyVariable::Date(date.variable()));
+    }
+
+    fn names(&mut self, names: &Names) -> Self::Output {
+        let refr = self.checker.refr;
+        let mut alternatives = names.variables.clone();
+        if let Some(subst) = &names.substitute {
+            self.substitute_names(&subst.0, &mut alternatives);
+        }
+        if alternatives
+            .iter()
+            .any(|&v| ref_has_variable(refr, AnyVariable::Name(v)))
+        {
+            return;
+        }
+        for &var in names.variables.iter() {
+            self.require(AnyVariable::Name(var));
+        }
+    }
+}
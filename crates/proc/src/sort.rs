@@ -21,6 +21,9 @@ fn plain_macro_element(macro_name: SmartString) -> TextElement {
         strip_periods: false,
         text_case: TextCase::None,
         display: None,
+        max_length: None,
+        page_range_format: None,
+        language: None,
     }
 }
 
@@ -261,6 +264,117 @@ enum SortValue {
     Date(Option<DateOrRange>),
 }
 
+/// Separates the keys in a `sort_key_string`. It sorts before any text, so a key that is a
+/// prefix of another comes first.
+const KEY_SEPARATOR: char = '\u{1F}';
+/// Stands in for a missing value, which sorts after everything else like in `Demoting`.
+const KEY_MISSING: char = char::MAX;
+
+impl SortValue {
+    fn write_lexical(&self, out: &mut String) {
+        use citeproc_io::{NumericToken, NumericValueOwned};
+        use natural_sort::{write_padded, write_padded_num};
+        match self {
+            SortValue::Macro(Some(m)) => m.write_lexical(out),
+            SortValue::Cnum(Some(n)) => write_padded_num(*n, out),
+            SortValue::OrdinaryVariable(Some(s)) => write_padded(s.as_str(), out),
+            SortValue::Number(Some(NumericValueOwned::Tokens(ts))) => {
+                for t in ts {
+                    match t {
+                        NumericToken::Num(n) | NumericToken::Roman(n, _) => {
+                            write_padded_num(*n, out)
+                        }
+                        NumericToken::Affixed(pre, n, suf) => {
+                            write_padded(pre, out);
+                            write_padded_num(*n, out);
+                            write_padded(suf, out);
+                        }
+                        NumericToken::Str(s) => write_padded(s, out),
+                        NumericToken::Comma => out.push(','),
+                        NumericToken::Hyphen => out.push('-'),
+                        NumericToken::Ampersand | NumericToken::And | NumericToken::CommaAnd => {
+                            out.push('&')
+                        }
+                    }
+                }
+            }
+            SortValue::Number(Some(NumericValueOwned::Str(s))) => write_padded(s, out),
+            SortValue::Names(Some(names)) => {
+                for (i, name) in names.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    write_padded(name.as_str(), out);
+                }
+            }
+            SortValue::Date(Some(date)) => write_lexical_date(date, out),
+            _ => out.push(KEY_MISSING),
+        }
+    }
+}
+
+/// YYYYMMDD, with the year offset so BC dates come first and seasons ignored.
+fn write_lexical_date(date: &DateOrRange, out: &mut String) {
+    use std::fmt::Write;
+    let ymd = |d: &citeproc_io::Date, out: &mut String| {
+        let month = if d.month > 12 { 0 } else { d.month };
+        let year = i64::from(d.year) + 100_000;
+        write!(out, "{:06}{:02}{:02}", year, month, d.day).unwrap();
+    };
+    match date {
+        DateOrRange::Single(d) => ymd(d, out),
+        DateOrRange::Range(a, b) => {
+            ymd(a, out);
+            out.push('/');
+            ymd(b, out);
+        }
+        DateOrRange::Literal { literal, .. } => natural_sort::write_padded(literal, out),
+    }
+}
+
+/// The bibliography sort keys of a reference as one string, for hosts that sort references
+/// themselves or want to show what they were sorted by. Comparing two of these as plain strings
+/// gives roughly the engine's own order: numbers are zero-padded, dates are written as
+/// `YYYYMMDD`, and missing values sort last. Descending keys are not reversed.
+///
+/// `None` if the style has no bibliography sort, or the reference is not in the bibliography.
+pub fn sort_key_string(db: &dyn IrDatabase, ref_id: Atom) -> Option<String> {
+    let style = db.style();
+    let sort = style.bibliography.as_ref()?.sort.as_ref()?;
+    let sorted_refs = db.sorted_refs();
+    let (ref ids, ref bib_numbers) = *sorted_refs;
+    let bib_number = *bib_numbers.get(&ref_id)?;
+    let max_cnum = ids.len() as u32;
+    let refr_arc = db.reference(ref_id.clone());
+    with_bib_context(
+        db,
+        ref_id,
+        refr_arc.as_deref(),
+        bib_number.cited_only(),
+        None,
+        None,
+        |_, mut ctx| {
+            let demoting = ctx_sort_items(
+                db,
+                CiteOrBib::Bibliography,
+                &mut ctx,
+                bib_number,
+                sort,
+                max_cnum,
+            );
+            let mut string = String::new();
+            for (i, item) in demoting.items.iter().enumerate() {
+                if i > 0 {
+                    string.push(KEY_SEPARATOR);
+                }
+                item.value.write_lexical(&mut string);
+            }
+            Some(string)
+        },
+        |_, _, _| None,
+    )
+}
+
 use std::cmp::Ordering;
 #[derive(Debug)]
 enum Demoted {
@@ -543,7 +657,7 @@ impl<'a, O: OutputFormat> StyleWalker for SortingWalker<'a, O> {
             }),
             StandardVariable::Ordinary(var) => self
                 .ctx
-                .get_ordinary(var, form)
+                .get_ordinary_variant(var, form, text.language.as_ref())
                 .map(|val| renderer.text_variable(text, svar, &val)),
         };
         let gv = GroupVars::rendered_if(res.is_some());
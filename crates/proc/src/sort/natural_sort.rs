@@ -288,6 +288,65 @@ impl Ord for NaturalCmp {
     }
 }
 
+impl NaturalCmp {
+    /// Writes the string so that comparing it with plain string comparison orders it (roughly)
+    /// the way `NaturalCmp` does. Numbers are zero-padded and dates are written as sortable
+    /// digits.
+    pub fn write_lexical(&self, out: &mut String) {
+        for token in (TokenIterator { remain: &self.0 }) {
+            match token {
+                Token::Str(s) => write_padded(s, out),
+                Token::Num(n) | Token::CitationNumber(n) => write_padded_num(n, out),
+                Token::Date(CmpRange::Single(a)) => write_cmp_date(&a, out),
+                Token::Date(CmpRange::Range(a, b)) => {
+                    write_cmp_date(&a, out);
+                    out.push('/');
+                    write_cmp_date(&b, out);
+                }
+            }
+        }
+    }
+}
+
+/// Years are offset so BC years sort before AD ones.
+const YEAR_OFFSET: i64 = 100_000;
+
+fn write_cmp_date(date: &CmpDate, out: &mut String) {
+    use std::fmt::Write;
+    let year = date.year.map_or(0, |y| i64::from(y) + YEAR_OFFSET);
+    write!(out, "{:06}{}", year, date.rest).unwrap();
+}
+
+pub(crate) fn write_padded_num(n: u32, out: &mut String) {
+    use std::fmt::Write;
+    write!(out, "{:010}", n).unwrap();
+}
+
+/// Lowercases `s` and zero-pads any runs of digits in it.
+pub(crate) fn write_padded(s: &str, out: &mut String) {
+    let mut rest = s;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        out.extend(rest[..start].chars().flat_map(char::to_lowercase));
+        let digits = &rest[start..];
+        let len = digits
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or_else(|| digits.len());
+        match digits[..len].parse() {
+            Ok(n) => write_padded_num(n, out),
+            Err(_) => out.push_str(&digits[..len]),
+        }
+        rest = &digits[len..];
+    }
+    out.extend(rest.chars().flat_map(char::to_lowercase));
+}
+
+#[test]
+fn lexical_pads_numbers() {
+    let mut out = String::new();
+    write_padded("Vol 9b", &mut out);
+    assert_eq!(out, "vol 0000000009b");
+}
+
 fn natural_cmp(a: &str, b: &str) -> Ordering {
     let a_i = TokenIterator { remain: a };
     let b_i = TokenIterator { remain: b };
@@ -108,8 +108,17 @@ pub trait StyleWalker {
     fn layout(&mut self, layout: &Layout) -> Self::Output {
         self.fold(&layout.elements, WalkerFoldType::Layout(layout))
     }
+    /// Walks the default citation layout followed by every locale layout, as one sequence.
     fn walk_citation(&mut self, style: &Style) -> Self::Output {
-        self.layout(&style.citation.layout)
+        let citation = &style.citation;
+        if citation.locale_layouts.is_empty() {
+            return self.layout(&citation.layout);
+        }
+        let elements: Vec<Element> = std::iter::once(&citation.layout)
+            .chain(citation.locale_layouts.iter())
+            .flat_map(|layout| layout.elements.iter().cloned())
+            .collect();
+        self.fold(&elements, WalkerFoldType::Layout(&citation.layout))
     }
     fn walk_bibliography(&mut self, style: &Style) -> Option<Self::Output> {
         style
@@ -108,6 +108,7 @@ impl Driver {
                 format_options: options.format_options,
                 bibliography_no_sort: options.bibliography_no_sort,
                 locale_override: options.locale_override,
+                bibliography_locale_override: options.bibliography_locale_override,
                 test_mode: false,
                 csl_features: Some(csl_features),
                 ..Default::default()
@@ -364,19 +365,16 @@ impl Driver {
         })
     }
 
-    /// Returns all the clusters and bibliography entries in the document.
+    /// Returns all the clusters and bibliography entries in the document, with any warnings
+    /// from rendering them (terms missing from a locale in use, macros that are never called,
+    /// reference fields that are not CSL variables).
     /// Also drains the queue, just like batchedUpdates().
     /// Use this to rehydrate a document or run non-interactively.
     #[wasm_bindgen(js_name = "fullRender")]
     pub fn full_render(&self) -> FullRenderResult {
         typescript_serde_result(|| {
             let mut eng = self.engine.borrow_mut();
-            let all_clusters = eng.all_clusters_str();
-            let bib_entries = eng.get_bibliography();
-            let all = string_id::FullRender {
-                all_clusters,
-                bib_entries,
-            };
+            let all = eng.full_render_str();
             eng.drain();
             Ok(all)
         })
@@ -463,7 +461,7 @@ interface InitOptions {
     fetcher?: Fetcher,
 
     /** The output format for this driver instance */
-    format: "html" | "rtf" | "plain",
+    format: "html" | "rtf" | "plain" | "latex" | "typst" | "asciidoc",
 
     /** A locale to use instead of the style's default-locale.
       *
@@ -568,9 +566,15 @@ type BibEntry = {
 
 type BibEntries = BibEntry[];
 
+type Warning =
+    | { kind: "missingTerm", term: string, lang: string }
+    | { kind: "unusedMacro", name: string }
+    | { kind: "unknownVariable", id: string, name: string };
+
 type FullRender = {
     allClusters: Map<string, string>,
     bibEntries: BibEntries,
+    warnings: Warning[],
 };
 
 type BibliographyMeta = {
@@ -35,6 +35,9 @@ pub struct WasmInitOptions {
     /// You might get this from a dependent style via `StyleMeta::parse(dependent_xml_string)`
     #[serde(default)]
     pub locale_override: Option<Lang>,
+    /// Renders the bibliography in a different language to the citations.
+    #[serde(default)]
+    pub bibliography_locale_override: Option<Lang>,
     /// Disables sorting on the bibliography
     #[serde(default)]
     pub bibliography_no_sort: bool,